@@ -0,0 +1,15 @@
+use crate::*;
+use sha2::{Digest, Sha256};
+
+/// Write out a hash as lowercase hex.
+pub fn to_hex(hash: &[u8]) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Work out the SHA-256 checksum of a file, as lowercase hex.
+pub fn file_checksum(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hash = Sha256::new();
+    std::io::copy(&mut file, &mut hash)?;
+    Ok(to_hex(&hash.finalize()))
+}
@@ -0,0 +1,481 @@
+use crate::*;
+
+/// Run a whole import as the command line tool does, returning the exit code.
+/// Messages are logged with the `log` crate, so set up a logger first to see them.
+pub fn run(args: &Arguments) -> i32 {
+    // Earlier runs on this thread have counted their errors already.
+    let errors_before = thread_row_errors();
+    if let Some(&[rows, columns]) = args.benchmark.as_deref() {
+        if let Err(er) = run_benchmark(args, rows, columns) {
+            error!("Error running the benchmark: {}", er);
+            return 1;
+        }
+        return 0;
+    }
+
+    // Gather up the files to import, along with any table names given for them.
+    let mut inputs: Vec<(PathBuf, Option<String>)> = args.input.iter()
+        .map(|input| (PathBuf::from(input), None))
+        .collect();
+    if let Some(list) = args.input_list.as_ref() {
+        match read_input_list(Path::new(list)) {
+            Ok(listed) => inputs.extend(listed),
+            Err(er) => {
+                error!("Error reading the input list '{}': {}", list, er);
+                return 1;
+            },
+        }
+    }
+    if inputs.is_empty() {
+        error!("No input files to import!");
+        return 1;
+    }
+
+    if args.column_width_report {
+        let mut status = 0;
+        for (path, _) in &inputs {
+            if let Err(er) = column_width_report(args, path) {
+                error!("Error reading '{}': {}", path.display(), er);
+                status = 1;
+            }
+        }
+        return status;
+    }
+
+    if let Some(dir) = args.separate_databases.as_ref() {
+        return run_separately(args, Path::new(dir), &inputs, errors_before);
+    }
+
+    // Open the output file, whatever it is, then open the SQLite connection with it.
+    let to_stdout = args.output.as_deref() == Some("-");
+    let path: PathBuf = match (args.output.as_ref(), args.output_format) {
+        // A database has to be built in a real file before it can be copied to stdout.
+        (Some(_), OutputFormat::Sqlite) if to_stdout => {
+            std::env::temp_dir().join(format!("csv_to_sqlite-{}.db", std::process::id()))
+        },
+        (None, _) if inputs[0].0 == Path::new(STDIN) => {
+            error!("An --output path is needed when reading from stdin!");
+            return 1;
+        },
+        // Use the first input path + .db if no explicit output path is given.
+        (None, OutputFormat::Sqlite) => inputs[0].0.with_extension("db"),
+        (None, OutputFormat::Sql) => inputs[0].0.with_extension("sql"),
+        (Some(output), _) => PathBuf::from(output),
+    };
+
+    // A SQL script is built up in memory and written out at the end.
+    // A dry run works in memory, so nothing is written.
+    let conn = match args.output_format {
+        _ if args.dry_run => Connection::open_in_memory().map_err(ImportError::from),
+        OutputFormat::Sqlite if to_stdout => open_database(&path, OpenMode::New),
+        OutputFormat::Sqlite => open_database(&path, args.open_mode),
+        OutputFormat::Sql => Connection::open_in_memory().map_err(ImportError::from),
+    };
+    let conn = match conn {
+        Ok(conn) => conn,
+        Err(er) => {
+            error!("Error opening the database: {}", er);
+            return 1;
+        },
+    };
+    if args.compact && args.output_format == OutputFormat::Sqlite {
+        if let Err(er) = set_page_size(&conn, COMPACT_PAGE_SIZE) {
+            error!("Error setting the page size: {}", er);
+            return 1;
+        }
+    }
+
+    // The dedup report covers every file in this run.
+    let mut dedup_report = match args.dedup_report.as_ref().map(csv::Writer::from_path).transpose() {
+        Ok(report) => report,
+        Err(er) => {
+            error!("Error opening the dedup report: {}", er);
+            return 1;
+        },
+    };
+
+    let run_at = unix_time(args);
+    let mut runs = vec![];
+
+    if args.connection_pool > 1 && args.output_format == OutputFormat::Sqlite && !args.dry_run {
+        match import_in_parallel(&conn, args, &path, &inputs) {
+            Ok(parallel_runs) => runs = parallel_runs,
+            Err(er) => {
+                error!("Error setting up the connection pool: {}", er);
+                return 1;
+            },
+        }
+    } else {
+        if args.connection_pool > 1 {
+            warn!("--connection-pool needs a database file, so the files will be imported one at a time");
+        }
+        for (ii, (path, table_name)) in inputs.iter().enumerate() {
+            let table_name = choose_table_name(args, path, ii, table_name.as_ref());
+            let run = import_tracked(&conn, args, path, &table_name, dedup_report.as_mut());
+            let failed = run.status == RunStatus::Failed;
+            runs.push(run);
+            if failed && args.fail_fast {
+                break;
+            }
+        }
+    }
+    let failures = runs.iter()
+        .filter(|run| run.status == RunStatus::Failed)
+        .map(|run| &run.source)
+        .collect::<Vec<&PathBuf>>();
+
+    if args.summary_table && !args.dry_run {
+        if let Err(er) = write_import_runs(&conn, &runs, run_at) {
+            error!("Error writing the summary table: {}", er);
+        }
+    }
+
+    // Views go in once all of their tables are populated.
+    let mut view_failed = false;
+    for view in &args.create_views {
+        if args.dry_run {
+            report(args, format_args!("View '{}': {}", view.name, view.select));
+            continue;
+        }
+        if let Err(er) = create_view(&conn, view) {
+            error!("Error creating view '{}': {}", view.name, er);
+            view_failed = true;
+        }
+    }
+
+    if args.summary_only {
+        report_error_summary(args, &thread_row_errors().since(&errors_before));
+    }
+
+    if let Some(mut report) = dedup_report {
+        if let Err(er) = report.flush() {
+            error!("Error writing the dedup report: {}", er);
+        }
+    }
+
+    if args.repl {
+        if to_stdout {
+            error!("--repl can't be used when the output is going to stdout");
+        }
+        else if let Err(er) = run_repl(&conn, args) {
+            error!("Error in the REPL: {}", er);
+        }
+    }
+
+    if args.compact && args.output_format == OutputFormat::Sqlite && !args.dry_run {
+        if let Err(er) = vacuum(&conn) {
+            error!("Error compacting the database: {}", er);
+        }
+    }
+
+    // A dry run has nothing to write.
+    if !args.dry_run {
+        if let Err(er) = write_output(conn, args, &path, to_stdout) {
+            error!("Error writing the output: {}", er);
+            return 1;
+        }
+    }
+
+    if !failures.is_empty() {
+        report_failures(&failures, inputs.len());
+        return 1;
+    }
+    if view_failed {
+        return 1;
+    }
+    if args.warnings_as_errors && total_warnings() > 0 {
+        error!("{} warnings (set RUST_LOG=warn to see them), and --warnings-as-errors is set", total_warnings());
+        return 1;
+    }
+
+    0
+}
+
+/// Import each file into a database of its own, for --separate-databases, returning the exit code.
+/// The master script is written even if some files fail, so the rest can still be used.
+fn run_separately(args: &Arguments, dir: &Path, inputs: &[(PathBuf, Option<String>)], errors_before: ErrorCounts) -> i32 {
+    if let Err(er) = std::fs::create_dir_all(dir) {
+        error!("Error creating '{}': {}", dir.display(), er);
+        return 1;
+    }
+
+    let mut dedup_report = match args.dedup_report.as_ref().map(csv::Writer::from_path).transpose() {
+        Ok(report) => report,
+        Err(er) => {
+            error!("Error opening the dedup report: {}", er);
+            return 1;
+        },
+    };
+
+    let mut failures = vec![];
+    let mut databases = vec![];
+    for (ii, (path, table_name)) in inputs.iter().enumerate() {
+        let table_name = choose_table_name(args, path, ii, table_name.as_ref());
+        let file_name = format!("{}.db", sanitize_identifier(&table_name));
+        let result = open_database(&dir.join(&file_name), args.open_mode)
+            .and_then(|conn| {
+                if args.compact {
+                    set_page_size(&conn, COMPACT_PAGE_SIZE)?;
+                }
+                import_file(&conn, args, path, &table_name, dedup_report.as_mut(), None)?;
+                if args.compact {
+                    vacuum(&conn)?;
+                }
+                Ok(())
+            });
+        match result {
+            Ok(()) => {
+                if !databases.iter().any(|(name, _)| *name == file_name) {
+                    databases.push((file_name, sanitize_identifier(&table_name)));
+                }
+            },
+            Err(er) => {
+                error!("Error importing '{}': {}", path.display(), er);
+                failures.push(path);
+                if args.fail_fast {
+                    break;
+                }
+            },
+        }
+    }
+
+    let master = dir.join("master.sql");
+    if let Err(er) = write_master_script(&master, &databases, &args.create_views) {
+        error!("Error writing '{}': {}", master.display(), er);
+        return 1;
+    }
+
+    if args.summary_only {
+        report_error_summary(args, &thread_row_errors().since(&errors_before));
+    }
+    if let Some(mut report) = dedup_report {
+        if let Err(er) = report.flush() {
+            error!("Error writing the dedup report: {}", er);
+        }
+    }
+    if !failures.is_empty() {
+        report_failures(&failures, inputs.len());
+        return 1;
+    }
+    0
+}
+
+/// Write a script which ATTACHes each (file name, schema name) database, then creates the views as TEMP views,
+/// since a view in one database can't refer to tables in another.
+/// The file names are relative, so the script has to be run from the directory the databases are in.
+fn write_master_script(path: &Path, databases: &[(String, String)], views: &[ViewDefinition]) -> std::io::Result<()> {
+    let mut out = std::io::BufWriter::new(File::create(path)?);
+    for (file_name, schema) in databases {
+        writeln!(out, "ATTACH DATABASE {} AS {};", sql_literal(&Value::Text(file_name.clone())), quote_identifier(schema))?;
+    }
+    for view in views {
+        writeln!(out, "CREATE TEMP VIEW {} AS {};", quote_identifier(&view.name), view.select)?;
+    }
+    out.flush()
+}
+
+/// Log the files which failed to import.
+fn report_failures(failures: &[&PathBuf], total: usize) {
+    error!("{} of {} files failed to import:", failures.len(), total);
+    for path in failures {
+        error!("    {}", path.display());
+    }
+}
+
+/// Work out the table name for the `index`th input file.
+/// The manifest's table name wins, then --table-name, then --table-name-pattern, then the file name.
+fn choose_table_name(args: &Arguments, path: &Path, index: usize, listed: Option<&String>) -> String {
+    match (listed.or(args.table_name.as_ref()), args.table_name_pattern.as_ref()) {
+        (Some(value), _) => value.clone(),
+        (None, Some(pattern)) => expand_table_name_pattern(pattern, path, index + 1, args),
+        (None, None) if path == Path::new(STDIN) => "stdin".to_string(),
+        (None, None) => format!("{}", basename(path).display()),
+    }
+}
+
+/// Import the input files on several threads at once, for --connection-pool.
+/// Returns how each file went, in the order they were given.
+fn import_in_parallel(conn: &Connection, args: &Arguments, path: &Path, inputs: &[(PathBuf, Option<String>)]) -> Result<Vec<FileRun>, ImportError> {
+    let pool = ConnectionPool::new(conn, path, Duration::from_millis(args.busy_timeout))?;
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let runs = Mutex::new(vec![]);
+
+    std::thread::scope(|scope| {
+        let mut workers = vec![];
+        for _ in 0..args.connection_pool.min(inputs.len()) {
+            workers.push(scope.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    let ii = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((path, table_name)) = inputs.get(ii) else {
+                        break;
+                    };
+                    let table_name = choose_table_name(args, path, ii, table_name.as_ref());
+                    let run = match pool.get() {
+                        Ok(conn) => import_tracked(&conn, args, path, &table_name, None),
+                        Err(er) => {
+                            error!("Error importing '{}': {}", path.display(), er);
+                            FileRun {
+                                source: path.clone(),
+                                table_name,
+                                status: RunStatus::Failed,
+                                rows: 0,
+                                errors: 0,
+                                message: Some(er.to_string()),
+                            }
+                        },
+                    };
+                    if run.status == RunStatus::Failed && args.fail_fast {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    runs.lock().unwrap().push((ii, run));
+                }
+                thread_row_errors()
+            }));
+        }
+        // The workers' row errors count towards this run, as if they'd happened on this thread.
+        for worker in workers {
+            credit_thread_row_errors(worker.join().unwrap());
+        }
+    });
+    pool.close(conn)?;
+
+    let mut runs = runs.into_inner().unwrap();
+    runs.sort_by_key(|(ii, _)| *ii);
+    Ok(runs.into_iter().map(|(_, run)| run).collect())
+}
+
+/// Finish off the output once everything has been imported.
+/// SQL scripts are dumped from the in-memory database, and a database going to stdout is copied out of its temporary file.
+fn write_output(conn: Connection, args: &Arguments, path: &Path, to_stdout: bool) -> Result<(), ImportError> {
+    match args.output_format {
+        OutputFormat::Sqlite if to_stdout => {
+            conn.close().map_err(|(_, er)| er)?;
+            let copied = File::open(path).and_then(|mut file| std::io::copy(&mut file, &mut std::io::stdout().lock()));
+            std::fs::remove_file(path)?;
+            copied?;
+        },
+        OutputFormat::Sqlite => (),
+        OutputFormat::Sql if to_stdout => {
+            let mut out = std::io::stdout().lock();
+            dump_sql(&conn, &mut out)?;
+            out.flush()?;
+        },
+        OutputFormat::Sql => {
+            let mut out = std::io::BufWriter::new(File::create(path)?);
+            dump_sql(&conn, &mut out)?;
+            out.flush()?;
+        },
+    }
+    Ok(())
+}
+
+/// Open the output database according to the --open-mode.
+pub fn open_database(path: &Path, mode: OpenMode) -> Result<Connection, ImportError> {
+    let flags = match mode {
+        OpenMode::Create => OpenFlags::default(),
+        OpenMode::Existing => OpenFlags::default() - OpenFlags::SQLITE_OPEN_CREATE,
+        OpenMode::New => {
+            // SQLite has no flag for this, so check for ourselves.
+            if path.exists() {
+                return Err(ImportError::Other(format!("'{}' already exists!", path.display())));
+            }
+            OpenFlags::default()
+        },
+        OpenMode::ReadOnly => {
+            (OpenFlags::default() - OpenFlags::SQLITE_OPEN_READ_WRITE - OpenFlags::SQLITE_OPEN_CREATE)
+                | OpenFlags::SQLITE_OPEN_READ_ONLY
+        },
+    };
+    Ok(Connection::open_with_flags(path, flags)?)
+}
+
+/// Read a list of input files, one per line.
+/// Each line may have a table name after a tab. Blank lines and lines starting with '#' are ignored.
+/// Relative paths are taken to be relative to the list file itself.
+fn read_input_list(list: &Path) -> std::io::Result<Vec<(PathBuf, Option<String>)>> {
+    let contents = std::fs::read_to_string(list)?;
+    let base = list.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut inputs = vec![];
+    for line in contents.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (input, table_name) = match line.split_once('\t') {
+            Some((input, table_name)) if !table_name.trim().is_empty() => (input, Some(table_name.trim().to_string())),
+            Some((input, _)) => (input, None),
+            None => (line, None),
+        };
+        inputs.push((base.join(input.trim()), table_name));
+    }
+    Ok(inputs)
+}
+
+/// Remove the suffix and parent directories from a path to get a basename.
+fn basename(path: &Path) -> PathBuf {
+    let noext_path = path.with_extension("");
+    let noparent_path =  noext_path.file_name().unwrap();
+    PathBuf::from(noparent_path)
+}
+
+/// Substitute the --table-name-pattern tokens for a file, then make the result a valid identifier.
+fn expand_table_name_pattern(pattern: &str, path: &Path, index: usize, args: &Arguments) -> String {
+    let parent = path.parent()
+        .and_then(|parent| parent.file_name())
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = pattern
+        .replace("{basename}", &basename(path).to_string_lossy())
+        .replace("{parent}", &parent)
+        .replace("{index}", &index.to_string())
+        .replace("{date}", &today(args));
+    sanitize_identifier(&name)
+}
+
+/// Replace anything that isn't a letter, digit or underscore with an underscore.
+/// Identifiers can't start with a digit, so those get an underscore in front.
+pub fn sanitize_identifier(name: &str) -> String {
+    let mut result = name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
+        .collect::<String>();
+    if result.is_empty() || result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result
+}
+
+/// The current time, in seconds since the Unix epoch.
+/// This is always 0 with --deterministic.
+pub fn unix_time(args: &Arguments) -> u64 {
+    if args.deterministic {
+        return 0;
+    }
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Today's date (UTC) as YYYYMMDD.
+fn today(args: &Arguments) -> String {
+    let seconds = unix_time(args);
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    format!("{:04}{:02}{:02}", year, month, day)
+}
+
+/// Convert days since 1970-01-01 into a (year, month, day) date.
+/// See Howard Hinnant's "chrono-Compatible Low-Level Date Algorithms".
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
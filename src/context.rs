@@ -0,0 +1,64 @@
+use crate::*;
+use std::cell::RefCell;
+
+/// Shows the raw lines around a row that couldn't be read or inserted, for --error-context.
+/// The file is only read again the first time it's needed, and then kept for any later errors.
+pub struct ErrorContext {
+    path: PathBuf,
+
+    /// How many lines to show either side of the problem.
+    lines: usize,
+
+    /// The lines of the file, once they've been read.
+    source: RefCell<Option<Vec<String>>>,
+
+    /// Whether there are --redact columns. Their values could be on any line, so only the line number is shown.
+    hidden: bool,
+}
+
+impl ErrorContext {
+    /// Returns None unless --error-context is set.
+    pub fn new(args: &Arguments, path: &Path) -> Option<Self> {
+        if args.error_context == 0 {
+            return None;
+        }
+        Some(ErrorContext {
+            path: path.to_path_buf(),
+            lines: args.error_context,
+            source: RefCell::new(None),
+            hidden: !args.redactions.is_empty(),
+        })
+    }
+
+    /// Log the lines around a 1-based line number, marking the line itself.
+    /// With --redact, only the line number is logged.
+    pub fn show(&self, line: u64) {
+        if self.hidden {
+            error!("at line {} of '{}' (not shown, since it may hold --redact values)", line.max(1), self.path.display());
+            return;
+        }
+        let mut source = self.source.borrow_mut();
+        if source.is_none() {
+            match std::fs::read(&self.path) {
+                Ok(bytes) => {
+                    let text = String::from_utf8_lossy(&bytes);
+                    *source = Some(text.lines().map(|l| l.to_string()).collect());
+                },
+                Err(er) => {
+                    warn!("can't re-read '{}' to show the error context: {}", self.path.display(), er);
+                    return;
+                },
+            }
+        }
+        let source = source.as_ref().unwrap();
+
+        let line = line.max(1) as usize;
+        let first = line.saturating_sub(self.lines).max(1);
+        let last = (line + self.lines).min(source.len());
+        error!("around line {} of '{}':", line, self.path.display());
+        for number in first..=last {
+            let marker = if number == line { ">" } else { " " };
+            error!("{} {:>6} | {}", marker, number, source[number - 1]);
+        }
+    }
+}
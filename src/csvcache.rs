@@ -1,4 +1,9 @@
 use crate::*;
+use std::{
+    collections::{HashMap, HashSet},
+    io::{BufRead, BufReader, Cursor, Read},
+    str::FromStr,
+};
  
 #[derive(Debug)]
 pub struct CSVCache {
@@ -17,6 +22,23 @@ pub struct CSVCache {
 
     /// Column name used for otherwise unnamed columns.
     default_column_name: String,
+
+    /// The source line each row started on.
+    lines: Vec<u64>,
+
+    /// Rows dropped as duplicates, as (source line, key) pairs.
+    /// This is only populated with --dedup.
+    duplicates: Vec<(u64, Vec<String>)>,
+
+    /// How many bytes of the source were read.
+    bytes_read: u64,
+
+    /// Header names changed by --clean-headers and the --header options, as (original, new) pairs.
+    renamed_headers: Vec<(String, String)>,
+
+    /// Cells which have no value at all, as (row, column) pairs, like the gaps left by --pivot.
+    /// These are stored as NULL whatever their column's type.
+    missing: HashSet<(usize, usize)>,
 }
 
 impl Default for CSVCache {
@@ -26,28 +48,117 @@ impl Default for CSVCache {
             rows: vec![vec![]],
             max_column_count: 0,
             default_column_name: String::from(""),
+            lines: vec![],
+            duplicates: vec![],
+            bytes_read: 0,
+            renamed_headers: vec![],
+            missing: HashSet::new(),
         }
     }
 }
 
 impl CSVCache {
-    pub fn load(args: &Arguments, path: &PathBuf) -> Result<CSVCache, csv::Error> {
+    pub fn load(args: &Arguments, path: &Path) -> Result<CSVCache, csv::Error> {
+        CSVCache::load_with_progress(args, path, "", None)
+    }
+
+    /// Load a file, reporting progress to a callback as well.
+    /// `table_name` is only used to fill in the progress.
+    pub fn load_with_progress(args: &Arguments, path: &Path, table_name: &str, on_progress: Option<ProgressCallback>) -> Result<CSVCache, csv::Error> {
+        // The header line isn't a row, so leave it out of the estimate.
+        let estimate = if args.estimate_rows {
+            Some(estimate_rows(path)?.saturating_sub(args.use_header as u64))
+        } else {
+            None
+        };
+        let mut progress = Progress::new(args, "Reading", estimate)
+            .with_callback(table_name, ImportStage::Reading, on_progress);
+        let context = ErrorContext::new(args, path);
+        let cache = CSVCache::read_checked(args, File::open(path)?, &mut progress, context.as_ref())?;
+        progress.finish();
+        Ok(cache)
+    }
+
+    /// Load a CSV file from anything which can be read, like stdin or a `&[u8]` in memory.
+    pub fn from_reader<R: Read>(args: &Arguments, reader: R) -> Result<CSVCache, csv::Error> {
+        let mut progress = Progress::new(args, "Reading", None);
+        let cache = CSVCache::read_checked(args, reader, &mut progress, None)?;
+        progress.finish();
+        Ok(cache)
+    }
+
+    /// Read a whole source.
+    /// The delimiter is worked out from the start of the source, as --input-format says.
+    fn read_checked<R: Read>(args: &Arguments, source: R, progress: &mut Progress, context: Option<&ErrorContext>) -> Result<CSVCache, csv::Error> {
+        let mut source = BufReader::with_capacity(SNIFF_BYTES, source);
+        let delimiter = input_delimiter(args, source.fill_buf()?)?;
+        CSVCache::read(args, source, delimiter, 0, progress, context)
+    }
+
+    /// Load each section of a file as its own cache, for --multi-section.
+    /// Sections are separated by blank lines. If the first line of a section has no delimiter in it, it's taken
+    /// as the section's title rather than its header, and returned alongside the cache.
+    /// Quoted fields containing blank lines will be split into separate sections.
+    /// Progress is reported against `table_name`, since the sections' own names aren't known until they're read.
+    pub fn load_sections(args: &Arguments, path: &Path, table_name: &str, on_progress: Option<ProgressCallback>) -> Result<Vec<(Option<String>, CSVCache)>, csv::Error> {
+        // The file isn't decoded here, so a section with bad UTF-8 in it is only a problem for the records affected.
+        let contents = std::fs::read(path)?;
+        let delimiter = input_delimiter(args, &contents[..contents.len().min(SNIFF_BYTES)])?;
+
+        // Split the file up, keeping track of where each section starts for error messages.
+        let mut sections: Vec<(u64, Vec<&[u8]>)> = vec![];
+        let mut in_section = false;
+        let lines = contents.split(|&b| b == b'\n').map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+        for (ii, line) in lines.enumerate() {
+            if line.iter().all(u8::is_ascii_whitespace) {
+                in_section = false;
+            }
+            else if in_section {
+                sections.last_mut().unwrap().1.push(line);
+            }
+            else {
+                sections.push((ii as u64, vec![line]));
+                in_section = true;
+            }
+        }
+
+        let mut result = vec![];
+        let mut progress = Progress::new(args, "Reading", None)
+            .with_callback(table_name, ImportStage::Reading, on_progress);
+        let context = ErrorContext::new(args, path);
+        for (first_line, mut lines) in sections {
+            let title = if lines.len() > 1 && !lines[0].contains(&delimiter) {
+                Some(String::from_utf8_lossy(lines.remove(0)).trim().to_string())
+            } else {
+                None
+            };
+            let line_offset = first_line + title.is_some() as u64;
+            let cache = CSVCache::read(args, lines.join(&b'\n').as_slice(), delimiter, line_offset, &mut progress, context.as_ref())?;
+            result.push((title, cache));
+        }
+        progress.finish();
+        Ok(result)
+    }
+
+    /// Read a CSV file from any source.
+    /// `line_offset` is added to line numbers, for sources which start partway through a file.
+    /// If there's a `context`, the lines around any record which can't be read are shown.
+    fn read<R: Read>(args: &Arguments, source: R, delimiter: u8, line_offset: u64, progress: &mut Progress, context: Option<&ErrorContext>) -> Result<CSVCache, csv::Error> {
         // Load the CSV reader with arguments.
         // TODO: error handling.
-        let mut reader = csv::ReaderBuilder::new()
-            .has_headers(args.use_header)
-            .delimiter(args.delimiter as u8)
-            .flexible(true)
-            .comment(Some('#' as u8))
-            .from_path(path)?;
+        let RecordSource { mut reader, comment_header, lines } = RecordSource::open(args, source, delimiter)?;
+        let line_offset = line_offset + lines;
 
         // Keep track of this throughout the function.
         let mut max_column_count = 0;
+        let mut renamed_headers = vec![];
+        let mut raw_header = None;
 
         // Check the arguments.
-        let mut header = if args.use_header {
+        let header = if args.use_header {
             // We need to populate the header.
             let val = match reader.headers() {
+                _ if comment_header.is_some() => comment_header,
                 Ok(headers) => {
                     Some(
                         headers.iter()
@@ -55,12 +166,24 @@ impl CSVCache {
                         .collect::<Vec<String>>()
                     )
                 },
-                Err(er) => {
-                    error!("Error while reading headers: {}", er);
-                    None
-                },
+                // Nothing after a header which can't be read can be trusted either.
+                Err(er) => return Err(er),
             };
 
+            if let Some(names) = val.as_ref() {
+                check_column_count(args, names.len(), "The header")?;
+            }
+            raw_header = val.clone();
+            let val = val.map(|names| names.iter()
+                .map(|name| {
+                    let rewritten = rewrite_header(name, args);
+                    if rewritten != *name {
+                        renamed_headers.push((name.clone(), rewritten.clone()));
+                    }
+                    rewritten
+                })
+                .collect::<Vec<String>>());
+
             // Set the max column count.
             max_column_count = match val.as_ref() { Some(x) => x.len(), None => 0 };
 
@@ -70,47 +193,273 @@ impl CSVCache {
             None
         };
 
+        // Work out which columns make up the dedup key.
+        let dedup_key = dedup_key_indices(args, header.as_deref())?;
+        let mut seen: HashSet<Vec<String>> = HashSet::new();
+        let mut duplicates = vec![];
+
         // Populate the rows.
         let mut rows = vec![];
-        for row in reader.records() {
+        let mut lines = vec![];
+        let mut data_rows = 0;
+        let mut repeated_headers = 0;
+        let bytes_before = progress.bytes();
+        let mut sample = args.sample_rows.map(|size| Reservoir::new(size, sample_seed(args)));
+        let mut records = reader.into_records();
+        let mut lookahead = None;
+        while let Some(row) = lookahead.take().or_else(|| records.next()) {
+            progress.set_bytes(bytes_before + records.reader().position().byte());
+            progress.tick(1);
             match row {
                 Ok(record) => {
-                    if !record.is_empty() {
+                    if !is_trailing_blank(&record, &mut records, &mut lookahead) {
+                        let is_header = raw_header.as_ref()
+                            .is_some_and(|header| record.iter().eq(header.iter().map(|h| h.as_str())));
+                        if args.skip_repeated_headers && is_header {
+                            warn!("dropping a repeat of the header on line {}", record.position().map_or(0, |pos| pos.line()) + line_offset);
+                            repeated_headers += 1;
+                            continue;
+                        }
+
+                        data_rows += 1;
+                        if header.is_none() && data_rows == 1 {
+                            let line = record.position().map_or(0, |pos| pos.line()) + line_offset;
+                            check_column_count(args, record.len(), &format!("The first row (line {})", line))?;
+                        }
+                        if let Some(range) = args.rows.as_ref() {
+                            if data_rows > range.end {
+                                // Nothing past here is wanted, so don't bother reading it.
+                                break;
+                            }
+                            if data_rows < range.start {
+                                continue;
+                            }
+                        }
+
+                        let line = record.position().map_or(0, |pos| pos.line()) + line_offset;
                         // Make a copy.
                         let record = record.iter()
                             .map(|x| x.to_string())
                             .collect::<Vec<String>>();
+
+                        if args.dedup {
+                            let key = match dedup_key.as_ref() {
+                                Some(indices) => indices.iter()
+                                    .map(|&ii| record.get(ii).cloned().unwrap_or_default())
+                                    .collect::<Vec<String>>(),
+                                None => record.clone(),
+                            };
+                            if !seen.insert(key.clone()) {
+                                debug!("dropping duplicate row on line {}", line);
+                                duplicates.push((line, redact_key(args, header.as_deref(), dedup_key.as_deref(), key)));
+                                continue;
+                            }
+                        }
+
+                        if let Some(sample) = sample.as_mut() {
+                            sample.offer((line, record));
+                            continue;
+                        }
+
                         // This might be a longer row.
                         max_column_count = max_column_count.max(record.len());
                         rows.push(record);
+                        lines.push(line);
                     }
-                    ()
+                },
+                Err(er) if er.is_io_error() || args.rfc4180 => {
+                    // The file itself can't be read (or doesn't conform), so there's no point carrying on.
+                    return Err(er);
                 },
                 Err(er) => {
-                    error!("Error reading CSV file: {}", er);
-                    ()
+                    let at = RowLocation { line: er.position().map(|pos| pos.line() + line_offset), ..Default::default() };
+                    row_error(args, ErrorCategory::Parse, at, format_args!("Error reading CSV file: {}", er));
+                    if let (Some(context), Some(pos), false) = (context, er.position(), args.summary_only) {
+                        context.show(pos.line() + line_offset);
+                    }
                 }
             }
         }
 
+        if repeated_headers > 0 {
+            warn!("dropped {} repeats of the header", repeated_headers);
+        }
+
+        // The sample goes back into the order it was in the file.
+        if let Some(sample) = sample {
+            let seen = sample.seen();
+            let mut sampled = sample.into_items();
+            info!("sampled {} of {} rows", sampled.len(), seen);
+            sampled.sort_by_key(|(line, _)| *line);
+            for (line, record) in sampled {
+                max_column_count = max_column_count.max(record.len());
+                rows.push(record);
+                lines.push(line);
+            }
+        }
+
+        // A key column going by its default name has to be in at least one row.
+        let missing_key = dedup_key.iter().flatten().zip(&args.dedup_key).find(|(index, _)| **index >= max_column_count);
+        if let Some((_, name)) = missing_key {
+            let message = format!("Dedup key column '{}' doesn't exist!", name);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, message).into());
+        }
+
         // Now, if the max_column_count is greater than the length of the header row, pad it.
-        if let Some(ref mut header) = header { 
-            if max_column_count > header.len() {
-                for ii in header.len() .. max_column_count {
-                    header.push(format!("{}{}", args.default_column_name, ii + 1));
-                }
+        // Without a header, every column gets its default name.
+        let mut header = header.unwrap_or_default();
+        if max_column_count > header.len() {
+            for ii in header.len() .. max_column_count {
+                header.push(format!("{}{}", args.default_column_name, ii + 1));
             }
         }
+        let header = Some(header);
 
         Ok(
             CSVCache { 
                 header, rows,
                 max_column_count,
                 default_column_name: args.default_column_name.to_string(),
+                lines,
+                duplicates,
+                bytes_read: progress.bytes() - bytes_before,
+                renamed_headers,
+                missing: HashSet::new(),
             }
         )
     }
 
+    /// Reshape a "long" file of entity, attribute and value columns into a wide one, for --pivot.
+    /// There's a row for each entity and a column for each attribute, in the order they first appear. Entities without
+    /// a value for an attribute get a missing cell, and if there's more than one value the last one is kept.
+    /// Each row's line is that of the entity's first row in the source.
+    pub fn pivot(&self, columns: &PivotColumns) -> Result<CSVCache, ImportError> {
+        let header = self.header();
+        let index = |name: &str, what: &str| header.iter()
+            .position(|h| *h == name)
+            .ok_or_else(|| ImportError::Other(format!("Pivot {} column '{}' doesn't exist!", what, name)));
+        let entity = index(&columns.entity, "entity")?;
+        let attribute = index(&columns.attribute, "attribute")?;
+        let value = index(&columns.value, "value")?;
+
+        let mut attributes: Vec<String> = vec![];
+        let mut attribute_indices: HashMap<&str, usize> = HashMap::new();
+        let mut entities: HashMap<&str, usize> = HashMap::new();
+        let mut cells: Vec<(&str, Vec<Option<&str>>)> = vec![];
+        let mut lines = vec![];
+        for (ii, row) in self.rows.iter().enumerate() {
+            let cell = |index: usize| row.get(index).map_or("", |value| value.as_str());
+            let column = *attribute_indices.entry(cell(attribute)).or_insert_with(|| {
+                attributes.push(cell(attribute).to_string());
+                attributes.len() - 1
+            });
+            let row_index = *entities.entry(cell(entity)).or_insert_with(|| {
+                cells.push((cell(entity), vec![]));
+                lines.extend(self.row_line(ii));
+                cells.len() - 1
+            });
+
+            let values = &mut cells[row_index].1;
+            if values.len() <= column {
+                values.resize(column + 1, None);
+            }
+            if values[column].is_some() {
+                warn!("'{}' has more than one '{}', so only the last one (line {}) is kept",
+                    cell(entity), cell(attribute), self.row_line(ii).unwrap_or_default());
+            }
+            values[column] = Some(cell(value));
+        }
+
+        let mut missing = HashSet::new();
+        let mut rows = vec![];
+        for (ii, (entity, values)) in cells.iter().enumerate() {
+            let mut row = vec![entity.to_string()];
+            for column in 0..attributes.len() {
+                match values.get(column).copied().flatten() {
+                    Some(value) => row.push(value.to_string()),
+                    None => {
+                        missing.insert((ii, column + 1));
+                        row.push(String::new());
+                    },
+                }
+            }
+            rows.push(row);
+        }
+
+        let mut header = vec![columns.entity.clone()];
+        header.extend(attributes);
+        Ok(CSVCache {
+            max_column_count: header.len(),
+            header: Some(header),
+            rows,
+            default_column_name: self.default_column_name.clone(),
+            lines,
+            duplicates: self.duplicates.clone(),
+            bytes_read: self.bytes_read,
+            renamed_headers: self.renamed_headers.clone(),
+            missing,
+        })
+    }
+
+    /// Rearrange the columns to match a schema, for --column-order-from-header-file.
+    /// Columns are matched up by name. Ones which aren't in the schema are dropped, unless `reject_extra` is set, when
+    /// they're an error; schema columns which aren't in the file get a missing cell in every row.
+    pub fn conform(&self, schema: &[SchemaColumn], reject_extra: bool) -> Result<CSVCache, ImportError> {
+        let indices = schema_indices(&self.header(), schema, reject_extra)?;
+        let mut missing = HashSet::new();
+        let rows = self.rows.iter()
+            .enumerate()
+            .map(|(ii, row)| indices.iter()
+                .enumerate()
+                .map(|(column, index)| match index {
+                    Some(index) if !self.is_missing(ii, *index) => row.get(*index).cloned().unwrap_or_default(),
+                    _ => {
+                        missing.insert((ii, column));
+                        String::new()
+                    },
+                })
+                .collect::<Vec<String>>())
+            .collect::<Vec<Vec<String>>>();
+
+        Ok(CSVCache {
+            max_column_count: schema.len(),
+            header: Some(schema.iter().map(|column| column.name.clone()).collect()),
+            rows,
+            default_column_name: self.default_column_name.clone(),
+            lines: self.lines.clone(),
+            duplicates: self.duplicates.clone(),
+            bytes_read: self.bytes_read,
+            renamed_headers: self.renamed_headers.clone(),
+            missing,
+        })
+    }
+
+    /// Header names which were rewritten, as (original, new) pairs.
+    pub fn renamed_headers(&self) -> &[(String, String)] {
+        &self.renamed_headers
+    }
+
+    /// How many bytes of the source were read.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Rows dropped by --dedup, as (source line, key) pairs.
+    pub fn duplicates(&self) -> &[(u64, Vec<String>)] {
+        &self.duplicates
+    }
+
+    /// Write the rows dropped by --dedup to a CSV writer.
+    /// Each record is the source name and line number, followed by the duplicated key.
+    pub fn write_dedup_report<W: std::io::Write>(&self, writer: &mut csv::Writer<W>, source: &str) -> Result<(), csv::Error> {
+        for (line, key) in &self.duplicates {
+            let mut record = vec![source.to_string(), line.to_string()];
+            record.extend(key.iter().cloned());
+            writer.write_record(&record)?;
+        }
+        Ok(())
+    }
+
     /// Find the length of the longest row.
     pub fn longest_row(&self) -> usize {
         let mut max_len = match self.header.as_ref(){ 
@@ -123,18 +472,37 @@ impl CSVCache {
         max_len
     }
 
-    pub fn rows_iter(&self) -> std::slice::Iter<Vec<String>> {
+    /// Get a row by its index, counting from 0 after the header.
+    pub fn row(&self, row: usize) -> Option<&[String]> {
+        self.rows.get(row).map(|r| r.as_slice())
+    }
+
+    /// The source line a row started on.
+    pub fn row_line(&self, row: usize) -> Option<u64> {
+        self.lines.get(row).copied()
+    }
+
+    /// Get a single cell by row and column index, both counting from 0.
+    /// Returns None if the row doesn't exist or is too short to have that column.
+    pub fn cell(&self, row: usize, col: usize) -> Option<&str> {
+        self.rows.get(row)?.get(col).map(|value| value.as_str())
+    }
+
+    /// Whether a cell has no value at all, rather than an empty one.
+    pub fn is_missing(&self, row: usize, col: usize) -> bool {
+        self.missing.contains(&(row, col))
+    }
+
+    pub fn rows_iter(&self) -> std::slice::Iter<'_, Vec<String>> {
         self.rows.iter()
     }
 
     pub fn header(&self) -> Vec<&str> {
-        if self.header.is_none() {
-            vec![]
-        }
-        else {
-            self.header.as_ref().unwrap().iter()
+        match self.header.as_ref() {
+            None => vec![],
+            Some(header) => header.iter()
                 .map(|x| x.as_ref())
-                .collect::<Vec<&str>>()
+                .collect::<Vec<&str>>(),
         }
     }
 
@@ -148,7 +516,7 @@ impl CSVCache {
 
         let mut result = vec![];
         for row in &self.rows {
-            result.push(row.get(column).and_then(|value| Some(value.as_str())))
+            result.push(row.get(column).map(|value| value.as_str()))
         }
         result
     }
@@ -169,3 +537,478 @@ impl CSVCache {
         (column_name.clone(), "TEXT".to_string())
     }
 }
+
+/// Match a header up with a schema, for --column-order-from-header-file, finding the column of the header each
+/// schema column comes from (if it's there at all). Header columns which aren't in the schema are dropped, unless
+/// `reject_extra` is set, when they're an error.
+pub fn schema_indices(header: &[&str], schema: &[SchemaColumn], reject_extra: bool) -> Result<Vec<Option<usize>>, ImportError> {
+    let extra = header.iter()
+        .filter(|name| !schema.iter().any(|column| column.name == **name))
+        .map(|name| format!("'{}'", name))
+        .collect::<Vec<String>>();
+    if !extra.is_empty() {
+        if reject_extra {
+            let (what, verb) = if extra.len() == 1 { ("Column", "isn't") } else { ("Columns", "aren't") };
+            return Err(ImportError::Other(format!("{} {} {} in the schema file!", what, extra.join(", "), verb)));
+        }
+        info!("dropping columns which aren't in the schema file: {}", extra.join(", "));
+    }
+
+    let indices = schema.iter()
+        .map(|column| header.iter().position(|h| *h == column.name))
+        .collect::<Vec<Option<usize>>>();
+    for (column, index) in schema.iter().zip(&indices) {
+        if index.is_none() {
+            info!("column '{}' isn't in the file, so it'll be NULL", column.name);
+        }
+    }
+    Ok(indices)
+}
+
+/// A CSV reader over a source, with everything the arguments say to put between the two.
+pub struct RecordSource<'a> {
+    pub reader: csv::Reader<Box<dyn Read + 'a>>,
+
+    /// The header read by --header-from-comment, if there was one. The reader doesn't read a header then.
+    pub comment_header: Option<Vec<String>>,
+
+    /// How many lines were used up before the reader's first line, to add to its line numbers.
+    pub lines: u64,
+}
+
+impl<'a> RecordSource<'a> {
+    /// Open a CSV reader over a source, with the header out of the way first for --header-from-comment, then
+    /// checking it's not too big for --max-field-size, and that it follows --rfc4180.
+    pub fn open<R: Read + 'a>(args: &Arguments, source: R, delimiter: u8) -> Result<Self, csv::Error> {
+        // With --header-from-comment, the header is taken out of the source before the CSV reader sees it.
+        let mut source = BufReader::new(source);
+        let CommentHeader { header: comment_header, pushed_back, lines } = if args.header_from_comment {
+            read_comment_header(&mut source, delimiter)?
+        } else {
+            CommentHeader::default()
+        };
+        let source = Cursor::new(pushed_back).chain(source);
+
+        let guard = FieldSizeGuard::new(source, args.max_field_size, delimiter);
+        // With --rfc4180, the source is checked as it's read, so the first deviation stops the whole file.
+        let guard: Box<dyn Read> = if args.rfc4180 {
+            Box::new(Rfc4180Check::new(guard, delimiter))
+        } else {
+            Box::new(guard)
+        };
+        let reader = reader_builder(args, delimiter, args.use_header && comment_header.is_none()).from_reader(guard);
+        Ok(RecordSource { reader, comment_header, lines })
+    }
+}
+
+/// Set up a CSV reader the way the arguments say.
+/// RFC 4180 has no comments and needs every record to be the same length.
+pub fn reader_builder(args: &Arguments, delimiter: u8, has_headers: bool) -> csv::ReaderBuilder {
+    let mut builder = csv::ReaderBuilder::new();
+    builder.has_headers(has_headers)
+        .delimiter(delimiter)
+        .flexible(!args.rfc4180)
+        .comment(if args.rfc4180 { None } else { Some(b'#') })
+        .terminator(if args.rfc4180 { csv::Terminator::CRLF } else { csv::Terminator::default() });
+    builder
+}
+
+/// What `read_comment_header` found.
+#[derive(Default)]
+struct CommentHeader {
+    header: Option<Vec<String>>,
+
+    /// A line which was read but isn't the header, to put back in front of the source.
+    pushed_back: Vec<u8>,
+
+    /// How many lines were used up.
+    lines: u64,
+}
+
+/// Read the header from the first line of a source if it's a comment, for --header-from-comment.
+/// Blank lines before it are skipped. If the first line isn't a comment, it's handed back to be read as usual.
+fn read_comment_header<R: BufRead>(source: &mut R, delimiter: u8) -> Result<CommentHeader, csv::Error> {
+    let mut lines = 0;
+    loop {
+        let mut line = vec![];
+        if source.read_until(b'\n', &mut line)? == 0 {
+            return Ok(CommentHeader { lines, ..Default::default() });
+        }
+        let text = String::from_utf8_lossy(&line);
+        if text.trim().is_empty() {
+            lines += 1;
+            continue;
+        }
+
+        let Some(names) = text.trim_start().strip_prefix('#') else {
+            warn!("there's no comment line to read the header from, so the first line is used");
+            return Ok(CommentHeader { header: None, pushed_back: line, lines });
+        };
+        let header = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(delimiter)
+            .trim(csv::Trim::All)
+            .from_reader(names.trim().as_bytes())
+            .records()
+            .next()
+            .transpose()?
+            .map(|record| record.iter().map(|name| name.to_string()).collect::<Vec<String>>());
+        return Ok(CommentHeader { header, pushed_back: vec![], lines: lines + 1 });
+    }
+}
+
+/// Whether a record is a blank last line, and so isn't a real row.
+/// The reader skips empty lines itself, including the one after a trailing newline, so files with and without a
+/// trailing newline read the same. But a last line of just spaces comes through as a single blank field, and would
+/// otherwise be imported as a phantom row. Anywhere else, a blank field is a value like any other (in a one-column
+/// file, say), and so is a quoted "" even on the last line.
+/// To find out whether a blank record is the last, the next one is read into `lookahead`, to be handled next.
+pub fn is_trailing_blank<R: Read>(record: &csv::StringRecord, records: &mut csv::StringRecordsIntoIter<R>, lookahead: &mut Option<csv::Result<csv::StringRecord>>) -> bool {
+    let blank = record.is_empty() || (record.len() == 1 && !record[0].is_empty() && record[0].trim().is_empty());
+    if !blank {
+        return false;
+    }
+    *lookahead = records.next();
+    lookahead.is_none()
+}
+
+/// An inclusive range of data rows to import, given with --rows as A:B.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl FromStr for RowRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once(':')
+            .ok_or_else(|| format!("expected A:B but got '{}'", s))?;
+        let parse = |bound: &str, default: u64| match bound.trim() {
+            "" => Ok(default),
+            bound => bound.parse::<u64>().map_err(|er| format!("bad row number '{}': {}", bound, er)),
+        };
+        let range = RowRange { start: parse(start, 1)?, end: parse(end, u64::MAX)? };
+        if range.start == 0 {
+            return Err("rows are numbered from 1".to_string());
+        }
+        if range.start > range.end {
+            return Err(format!("{} comes after {}", range.start, range.end));
+        }
+        Ok(range)
+    }
+}
+
+/// The columns of a "long" file to reshape with --pivot, given as ENTITY,ATTRIBUTE,VALUE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PivotColumns {
+    pub entity: String,
+    pub attribute: String,
+    pub value: String,
+}
+
+impl FromStr for PivotColumns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split(',').map(|name| name.trim()).collect::<Vec<&str>>().as_slice() {
+            [entity, attribute, value] if !entity.is_empty() && !attribute.is_empty() && !value.is_empty() => {
+                Ok(PivotColumns { entity: entity.to_string(), attribute: attribute.to_string(), value: value.to_string() })
+            },
+            _ => Err(format!("expected ENTITY,ATTRIBUTE,VALUE but got '{}'", s)),
+        }
+    }
+}
+
+/// Check the number of columns in the header or first row against --min-columns and --max-columns.
+pub fn check_column_count(args: &Arguments, count: usize, what: &str) -> Result<(), csv::Error> {
+    let columns = format!("{} has {} column{}", what, count, if count == 1 { "" } else { "s" });
+    let message = match (args.min_columns, args.max_columns) {
+        (Some(min), _) if count < min => format!("{}, fewer than --min-columns {}; is the delimiter right, or is the file cut off?", columns, min),
+        (_, Some(max)) if count > max => format!("{}, more than --max-columns {}; is the delimiter right?", columns, max),
+        _ => return Ok(()),
+    };
+    Err(std::io::Error::new(std::io::ErrorKind::InvalidData, message).into())
+}
+
+/// Find the column indices of the --dedup-key columns.
+/// Returns None if the whole row should be used as the key.
+fn dedup_key_indices(args: &Arguments, header: Option<&[String]>) -> Result<Option<Vec<usize>>, csv::Error> {
+    if args.dedup_key.is_empty() {
+        return Ok(None);
+    }
+
+    // Columns past the end of the header (which is all of them without one) go by their default names.
+    let header = header.unwrap_or(&[]);
+    let default_index = |name: &str| {
+        let number = name.strip_prefix(&args.default_column_name)?.parse::<usize>().ok()?;
+        (number > header.len()).then(|| number - 1)
+    };
+    let mut indices = vec![];
+    for name in &args.dedup_key {
+        match header.iter().position(|h| h == name).or_else(|| default_index(name)) {
+            Some(index) => indices.push(index),
+            None => {
+                let message = format!("Dedup key column '{}' doesn't exist!", name);
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, message).into());
+            }
+        }
+    }
+    Ok(Some(indices))
+}
+
+/// Redact the values of a dedup key which are from --redact columns, so the --dedup-report doesn't show them.
+/// `dedup_key` has the key's columns, or is None if the key is the whole row.
+fn redact_key(args: &Arguments, header: Option<&[String]>, dedup_key: Option<&[usize]>, mut key: Vec<String>) -> Vec<String> {
+    for (ii, value) in key.iter_mut().enumerate() {
+        let column = dedup_key.map_or(Some(ii), |indices| indices.get(ii).copied());
+        let name = column.and_then(|column| header?.get(column));
+        if let Some(mode) = name.and_then(|name| redaction_for(name, args)) {
+            *value = redact(mode, value, args);
+        }
+    }
+    key
+}
+
+/// A replacement to make in header names, given with --header-replace as FROM:TO.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderReplacement {
+    pub from: String,
+    pub to: String,
+}
+
+impl FromStr for HeaderReplacement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s.split_once(':')
+            .ok_or_else(|| format!("expected FROM:TO but got '{}'", s))?;
+        if from.is_empty() {
+            return Err("there's nothing to replace".to_string());
+        }
+        Ok(HeaderReplacement { from: from.to_string(), to: to.to_string() })
+    }
+}
+
+/// Apply --clean-headers, --header-strip-prefix, --header-strip-suffix and --header-replace to a header name.
+/// A name which would end up empty is left as it was, with a warning.
+pub fn rewrite_header(name: &str, args: &Arguments) -> String {
+    let mut rewritten = if args.clean_headers { clean_header(name) } else { name.to_string() };
+    if let Some(prefix) = args.header_strip_prefix.as_deref() {
+        rewritten = rewritten.strip_prefix(prefix).unwrap_or(&rewritten).to_string();
+    }
+    if let Some(suffix) = args.header_strip_suffix.as_deref() {
+        rewritten = rewritten.strip_suffix(suffix).unwrap_or(&rewritten).to_string();
+    }
+    for replacement in &args.header_replacements {
+        rewritten = rewritten.replace(&replacement.from, &replacement.to);
+    }
+
+    if rewritten.is_empty() {
+        warn!("rewriting header \"{}\" would leave it empty, so it's been left alone", name);
+        return name.to_string();
+    }
+    rewritten
+}
+
+/// Strip control and zero-width characters from a header name, for --clean-headers.
+/// Surrounding whitespace left behind is trimmed too, and a warning is logged if the name changes.
+fn clean_header(name: &str) -> String {
+    let cleaned = name.chars()
+        .filter(|&c| !c.is_control() && !matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}'))
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if cleaned != name {
+        warn!("cleaned header \"{}\" to \"{}\"", name.escape_debug(), cleaned);
+    }
+    cleaned
+}
+
+/// Checks that a source follows RFC 4180 to the letter as it's read, for --rfc4180.
+/// Records must end with CRLF, and quotes may only appear around whole fields (doubled to escape them).
+/// Everything up to the first deviation is read as usual, then reading fails with its line number from then on.
+struct Rfc4180Check<R: Read> {
+    inner: R,
+    delimiter: u8,
+    line: u64,
+    state: Rfc4180State,
+
+    /// What's wrong with the source, once something is.
+    problem: Option<String>,
+}
+
+/// Where an `Rfc4180Check` is up to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Rfc4180State {
+    /// At the start of a field, where a quote may open it.
+    FieldStart,
+    /// Partway through an unquoted field.
+    Unquoted,
+    /// Inside a quoted field.
+    Quoted,
+    /// Just after a quote inside a quoted field, which either closes it or escapes another quote. Unless another
+    /// quote follows, only a delimiter or the end of the record may come next.
+    QuoteInQuoted,
+    /// After a carriage return, which has to be followed by a line feed.
+    CarriageReturn,
+}
+
+impl<R: Read> Rfc4180Check<R> {
+    fn new(inner: R, delimiter: u8) -> Self {
+        Rfc4180Check { inner, delimiter, line: 1, state: Rfc4180State::FieldStart, problem: None }
+    }
+
+    /// Move on past one byte, or return what's wrong with it.
+    fn check(&mut self, b: u8) -> Result<(), &'static str> {
+        use Rfc4180State::*;
+        self.state = match (self.state, b) {
+            (Quoted, b'"') => QuoteInQuoted,
+            (Quoted, b'\n') => {
+                self.line += 1;
+                Quoted
+            },
+            (Quoted, _) => Quoted,
+            (QuoteInQuoted, b'"') => Quoted,
+            (CarriageReturn, b'\n') => {
+                self.line += 1;
+                FieldStart
+            },
+            (CarriageReturn, _) => return Err("carriage return without a line feed"),
+            (QuoteInQuoted, _) if b != self.delimiter && b != b'\r' => {
+                return Err("unexpected character after a closing quote");
+            },
+            (FieldStart, b'"') => Quoted,
+            (_, b'"') => return Err("quote in the middle of an unquoted field"),
+            (_, b'\r') => CarriageReturn,
+            (_, b'\n') => return Err("line ends with LF rather than CRLF"),
+            (_, _) if b == self.delimiter => FieldStart,
+            (_, _) => Unquoted,
+        };
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Rfc4180Check<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(problem) = self.problem.as_ref() {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, problem.clone()));
+        }
+
+        let count = self.inner.read(buf)?;
+        let problem = if count == 0 {
+            match self.state {
+                Rfc4180State::Quoted => Some((0, "quoted field is never closed")),
+                Rfc4180State::CarriageReturn => Some((0, "carriage return without a line feed")),
+                _ => None,
+            }
+        } else {
+            buf[..count].iter()
+                .enumerate()
+                .find_map(|(ii, &b)| self.check(b).err().map(|problem| (ii, problem)))
+        };
+
+        match problem {
+            // Anything before the deviation is still handed over, so the reader can finish the records before it.
+            Some((valid, problem)) => {
+                self.problem = Some(format!("not valid RFC 4180: line {}: {}", self.line, problem));
+                if valid > 0 {
+                    return Ok(valid);
+                }
+                self.read(buf)
+            },
+            None => Ok(count),
+        }
+    }
+}
+
+/// Stops the CSV reader from buffering an enormous field.
+/// An unclosed quote makes the reader treat the rest of the file as one field, so without this a
+/// malformed file can use up all of the memory before anything goes wrong.
+/// Fields end at a delimiter or line feed outside of quotes, as they do for the reader.
+struct FieldSizeGuard<R: Read> {
+    inner: R,
+
+    /// The limit set with --max-field-size.
+    limit: u64,
+
+    delimiter: u8,
+
+    /// Total bytes read so far.
+    bytes_read: u64,
+
+    /// Byte offset where the field being read started.
+    field_start: u64,
+
+    /// Whether the bytes read so far leave a quote open. An escaped quote opens and closes it again.
+    quoted: bool,
+
+    /// The error to give, once a field is too big.
+    problem: Option<String>,
+}
+
+impl<R: Read> FieldSizeGuard<R> {
+    fn new(inner: R, limit: u64, delimiter: u8) -> Self {
+        FieldSizeGuard { inner, limit, delimiter, bytes_read: 0, field_start: 0, quoted: false, problem: None }
+    }
+}
+
+impl<R: Read> Read for FieldSizeGuard<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(problem) = self.problem.as_ref() {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, problem.clone()));
+        }
+
+        let count = self.inner.read(buf)?;
+        for (ii, &b) in buf[..count].iter().enumerate() {
+            let offset = self.bytes_read + ii as u64;
+            if b == b'"' {
+                self.quoted = !self.quoted;
+            } else if !self.quoted && (b == self.delimiter || b == b'\n') {
+                self.field_start = offset + 1;
+                continue;
+            }
+            if offset - self.field_start >= self.limit {
+                self.problem = Some(format!(
+                    "the field starting at byte {} is bigger than --max-field-size ({} bytes); this is usually caused by an unclosed quote",
+                    self.field_start, self.limit,
+                ));
+                // The records before it are still handed over, so the reader can finish them.
+                self.bytes_read = offset;
+                return if ii > 0 { Ok(ii) } else { self.read(buf) };
+            }
+        }
+        self.bytes_read += count as u64;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_and_cells_by_index() {
+        let path = std::env::temp_dir().join(format!("csv_to_sqlite-cells-{}.csv", std::process::id()));
+        std::fs::write(&path, "name,age\nann,30\nbob\n").unwrap();
+        let args = Arguments::parse_from(["csv_to_sqlite".as_ref(), path.as_os_str()]);
+        let cache = CSVCache::load(&args, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(cache.row(0), Some(&["ann".to_string(), "30".to_string()][..]));
+        assert_eq!(cache.row(2), None);
+        assert_eq!(cache.cell(0, 1), Some("30"));
+        assert_eq!(cache.cell(1, 0), Some("bob"));
+        assert_eq!(cache.cell(1, 1), None);
+        assert_eq!(cache.cell(2, 0), None);
+    }
+
+    #[test]
+    fn loads_from_a_reader() {
+        let args = Arguments::parse_from(["csv_to_sqlite", "-"]);
+        let cache = CSVCache::from_reader(&args, "name,age\nann,30\nbob,40\n".as_bytes()).unwrap();
+        assert_eq!(cache.header(), ["name", "age"]);
+        assert_eq!(cache.get_nth_in_rows(0), [Some("ann"), Some("bob")]);
+    }
+}
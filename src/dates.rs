@@ -0,0 +1,177 @@
+/// A way of writing dates which --infer-dates recognises.
+/// Dates are stored as TEXT in ISO form ("YYYY-MM-DD", or "YYYY-MM-DD HH:MM:SS" with a time), which SQLite's date
+/// functions understand and which sorts in date order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFormat {
+    /// YYYY-MM-DD
+    Iso,
+    /// YYYY-MM-DD HH:MM:SS, with a 'T' or a space before the time.
+    IsoDateTime,
+    /// YYYY/MM/DD
+    YearFirst,
+    /// MM/DD/YYYY, as in the US.
+    MonthFirst,
+    /// MM/DD/YYYY HH:MM:SS
+    MonthFirstDateTime,
+    /// DD/MM/YYYY
+    DayFirst,
+    /// DD.MM.YYYY
+    DayFirstDotted,
+}
+
+impl DateFormat {
+    /// Every format, in the order they're preferred when a value could be in more than one of them.
+    /// That means "01/02/2024" is taken to be in January unless the column shows otherwise.
+    const ALL: [DateFormat; 7] = [
+        DateFormat::Iso,
+        DateFormat::IsoDateTime,
+        DateFormat::YearFirst,
+        DateFormat::MonthFirst,
+        DateFormat::MonthFirstDateTime,
+        DateFormat::DayFirst,
+        DateFormat::DayFirstDotted,
+    ];
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            DateFormat::Iso => "YYYY-MM-DD",
+            DateFormat::IsoDateTime => "YYYY-MM-DD HH:MM:SS",
+            DateFormat::YearFirst => "YYYY/MM/DD",
+            DateFormat::MonthFirst => "MM/DD/YYYY",
+            DateFormat::MonthFirstDateTime => "MM/DD/YYYY HH:MM:SS",
+            DateFormat::DayFirst => "DD/MM/YYYY",
+            DateFormat::DayFirstDotted => "DD.MM.YYYY",
+        }
+    }
+
+    /// Rewrite a value in this format in ISO form.
+    /// Returns None if the value isn't in this format, or isn't a real date (like February 30th).
+    pub fn to_iso(&self, value: &str) -> Option<String> {
+        let value = value.trim();
+        let (date, time) = match value.split_once([' ', 'T']) {
+            Some((date, time)) => (date, Some(time.trim_start())),
+            None => (value, None),
+        };
+        let has_time = matches!(self, DateFormat::IsoDateTime | DateFormat::MonthFirstDateTime);
+        if has_time != time.is_some() {
+            return None;
+        }
+
+        let (year, month, day) = match self {
+            DateFormat::Iso | DateFormat::IsoDateTime => {
+                let [year, month, day] = date_parts(date, '-')?;
+                (year, month, day)
+            },
+            DateFormat::YearFirst => {
+                let [year, month, day] = date_parts(date, '/')?;
+                (year, month, day)
+            },
+            DateFormat::MonthFirst | DateFormat::MonthFirstDateTime => {
+                let [month, day, year] = date_parts(date, '/')?;
+                (year, month, day)
+            },
+            DateFormat::DayFirst => {
+                let [day, month, year] = date_parts(date, '/')?;
+                (year, month, day)
+            },
+            DateFormat::DayFirstDotted => {
+                let [day, month, year] = date_parts(date, '.')?;
+                (year, month, day)
+            },
+        };
+        let year = number(year, 4, 4)?;
+        let month = number(month, 1, 2)?;
+        let day = number(day, 1, 2)?;
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        let mut iso = format!("{:04}-{:02}-{:02}", year, month, day);
+        if let Some(time) = time {
+            iso.push(' ');
+            iso.push_str(&iso_time(time)?);
+        }
+        Some(iso)
+    }
+}
+
+/// Split a date into its three numbers.
+fn date_parts(date: &str, separator: char) -> Option<[&str; 3]> {
+    date.split(separator).collect::<Vec<&str>>().try_into().ok()
+}
+
+/// Parse a number with between `min` and `max` digits.
+fn number(digits: &str, min: usize, max: usize) -> Option<u32> {
+    if digits.len() < min || digits.len() > max || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Rewrite a time like "9:05" or "09:05:30.25" as "HH:MM:SS", keeping any fraction of a second.
+fn iso_time(time: &str) -> Option<String> {
+    let mut parts = time.split(':');
+    let hour = number(parts.next()?, 1, 2)?;
+    let minute = number(parts.next()?, 2, 2)?;
+    let (second, fraction) = match parts.next() {
+        Some(seconds) => match seconds.split_once('.') {
+            Some((second, fraction)) if !fraction.is_empty() && fraction.chars().all(|c| c.is_ascii_digit()) => {
+                (number(second, 2, 2)?, fraction)
+            },
+            Some(_) => return None,
+            None => (number(seconds, 2, 2)?, ""),
+        },
+        None => (0, ""),
+    };
+    if parts.next().is_some() || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let mut iso = format!("{:02}:{:02}:{:02}", hour, minute, second);
+    if !fraction.is_empty() {
+        iso.push('.');
+        iso.push_str(fraction);
+    }
+    Some(iso)
+}
+
+/// Work out whether a column holds dates, for --infer-dates.
+/// It does if every non-empty value is a date in one of the formats; the format is then the one most of them are in.
+/// Values in other formats are stored as they are, with a warning, so they can be found and fixed.
+pub fn infer_date_format(values: &[Option<&str>]) -> Option<DateFormat> {
+    let mut counts = [0usize; DateFormat::ALL.len()];
+    let mut any = false;
+    for value in values.iter().flatten() {
+        if value.is_empty() {
+            continue;
+        }
+
+        let mut matched = false;
+        for (ii, format) in DateFormat::ALL.iter().enumerate() {
+            if format.to_iso(value).is_some() {
+                counts[ii] += 1;
+                matched = true;
+            }
+        }
+        if !matched {
+            return None;
+        }
+        any = true;
+    }
+    if !any {
+        return None;
+    }
+
+    // Ties go to the format which comes first.
+    let best = (0..counts.len()).fold(0, |best, ii| if counts[ii] > counts[best] { ii } else { best });
+    Some(DateFormat::ALL[best])
+}
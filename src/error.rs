@@ -0,0 +1,48 @@
+use std::fmt;
+
+/// Anything which can go wrong while importing a file.
+#[derive(Debug)]
+pub enum ImportError {
+    /// The CSV file couldn't be read.
+    Csv(csv::Error),
+
+    /// The SQLite database rejected something.
+    Sql(rusqlite::Error),
+
+    /// An output file couldn't be written.
+    Io(std::io::Error),
+
+    /// The file or arguments don't make sense.
+    Other(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Csv(er) => write!(f, "{}", er),
+            ImportError::Sql(er) => write!(f, "{}", er),
+            ImportError::Io(er) => write!(f, "{}", er),
+            ImportError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<csv::Error> for ImportError {
+    fn from(er: csv::Error) -> Self {
+        ImportError::Csv(er)
+    }
+}
+
+impl From<rusqlite::Error> for ImportError {
+    fn from(er: rusqlite::Error) -> Self {
+        ImportError::Sql(er)
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(er: std::io::Error) -> Self {
+        ImportError::Io(er)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::*;
+
+/// How many sample values to show for each column.
+const SAMPLES: usize = 3;
+
+/// A column as it would be created, with the reason it gets its type.
+pub struct ColumnPlan<'a> {
+    pub name: &'a str,
+    pub column_type: ColumnType,
+    pub reason: String,
+    pub collation: Option<&'a str>,
+}
+
+/// Print what importing a table would do, for --dry-run.
+/// With --explain, everything is shown: why each column gets its type (with some sample values), any header names
+/// which were rewritten or appear more than once, the transforms, and the SQL which would be run.
+pub fn explain_table(args: &Arguments, cached_csv: &CSVCache, table_name: &str, columns: &[ColumnPlan], pipelines: &[Vec<Operation>], rows: &[Vec<String>]) {
+    report(args, format_args!("Table '{}': {} rows, {} columns", table_name, rows.len(), columns.len()));
+    if !args.explain {
+        return;
+    }
+
+    if !cached_csv.duplicates().is_empty() {
+        report(args, format_args!("  {} duplicate rows dropped by --dedup", cached_csv.duplicates().len()));
+    }
+    for (original, renamed) in cached_csv.renamed_headers() {
+        report(args, format_args!("  header \"{}\" renamed to \"{}\"", original.escape_debug(), renamed));
+    }
+    for (ii, column) in columns.iter().enumerate() {
+        let count = columns.iter().filter(|c| c.name == column.name).count();
+        if count > 1 && columns[..ii].iter().all(|c| c.name != column.name) {
+            report(args, format_args!("  column \"{}\" appears {} times, so the table can't be created", column.name, count));
+        }
+    }
+
+    report(args, format_args!("  Columns:"));
+    for (ii, column) in columns.iter().enumerate() {
+        let samples = rows.iter()
+            .filter_map(|row| row.get(ii))
+            .filter(|value| !value.is_empty())
+            .take(SAMPLES)
+            .map(|value| format!("'{}'", value))
+            .collect::<Vec<String>>();
+        report(args, format_args!("    {} {} ({}); e.g. {}", quote_identifier(column.name), column_definition(column.column_type, column.collation), column.reason,
+            if samples.is_empty() { "nothing".to_string() } else { samples.join(", ") }));
+        if let Some(operations) = pipelines.get(ii).filter(|operations| !operations.is_empty()) {
+            report(args, format_args!("      transformed by {:?}", operations));
+        }
+    }
+
+    let definitions = columns.iter()
+        .map(|column| column_definition(column.column_type, column.collation))
+        .collect::<Vec<String>>();
+    let generated = args.generated_columns.iter()
+        .map(|generated| generated.definition())
+        .collect::<Vec<String>>();
+    for (column, definition) in args.generated_columns.iter().zip(&generated) {
+        report(args, format_args!("    {} {}", quote_identifier(&column.name), definition));
+    }
+
+    let mut table_columns = columns.iter()
+        .zip(&definitions)
+        .map(|(column, definition)| (column.name, definition.as_str()))
+        .collect::<Vec<(&str, &str)>>();
+    table_columns.extend(args.generated_columns.iter().zip(&generated).map(|(column, definition)| (column.name.as_str(), definition.as_str())));
+    let names = columns.iter().map(|column| column.name).collect::<Vec<&str>>();
+    report(args, format_args!("  SQL:"));
+    report(args, format_args!("    {}", build_create_table_query(table_name, &table_columns, TableKey::from_args(args)).trim()));
+    report(args, format_args!("    {};", build_insert_query(table_name, &names, None).trim_end_matches([' ', ';'])));
+}
+
+/// Print the evidence behind each column's type, for --explain-types: how many of its values are integers, reals,
+/// text and NULL, and the first value which made it wider.
+pub fn explain_types(args: &Arguments, table_name: &str, columns: &[ColumnPlan], evidence: &[TypeEvidence]) {
+    report(args, format_args!("Types for table '{}':", table_name));
+    for (column, evidence) in columns.iter().zip(evidence) {
+        report(args, format_args!("  {} {} ({}): {} integer, {} real, {} text, {} null",
+            quote_identifier(column.name), column.column_type.as_sql(), column.reason,
+            evidence.integers, evidence.reals, evidence.texts, evidence.nulls));
+        for (column_type, row, value) in &evidence.widened {
+            report(args, format_args!("    widened to {} by '{}' in row #{}", column_type.as_sql(), value, row));
+        }
+    }
+}
+
+/// Print a line of a report like --explain.
+/// Reports go to stdout, unless the database itself is going there with `--output -`: then they go to stderr, so they
+/// can't end up mixed into it.
+pub fn report(args: &Arguments, line: std::fmt::Arguments) {
+    if args.output.as_deref() == Some("-") {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
@@ -0,0 +1,534 @@
+use crate::*;
+
+/// Import a single CSV file into a table, returning how many rows went in.
+/// That's None if the file was skipped by --skip-if-unchanged.
+pub fn import_file(conn: &Connection, args: &Arguments, path: &Path, table_name: &str, mut dedup_report: Option<&mut csv::Writer<File>>, mut on_progress: Option<ProgressCallback>) -> Result<Option<u64>, ImportError> {
+    if args.open_mode == OpenMode::ReadOnly {
+        return Err(ImportError::Other("the database was opened read-only".to_string()));
+    }
+    let _file = log_file(path);
+    let _table = log_table(table_name);
+    let warnings_before = thread_warnings();
+
+    // Compare the file with the last import before going to the trouble of reading it.
+    let from_stdin = path == Path::new(STDIN);
+    let checksum = if (args.verify_checksum || args.skip_if_unchanged) && !from_stdin && !args.dry_run {
+        let checksum = file_checksum(path)?;
+        let source = checksum_source(path);
+        if args.skip_if_unchanged && stored_checksum(conn, &source, table_name)?.as_deref() == Some(checksum.as_str()) {
+            info!("skipping '{}', since it hasn't changed since it was last imported", path.display());
+            return Ok(None);
+        }
+        Some((source, checksum))
+    } else {
+        None
+    };
+
+    // With --streaming, the file is never held in memory all at once.
+    if args.streaming {
+        if from_stdin {
+            return Err(ImportError::Other("--streaming reads the file twice, so it can't read from stdin".to_string()));
+        }
+        let rows = stream_csv(conn, args, path, table_name, reborrow_callback(&mut on_progress))?;
+        return finish_import(conn, args, table_name, checksum, warnings_before, rows);
+    }
+
+    // Read the CSV file. With --multi-section, each section becomes a table of its own.
+    let tables = if from_stdin {
+        if args.multi_section {
+            return Err(ImportError::Other("--multi-section can't read from stdin".to_string()));
+        }
+        vec![(table_name.to_string(), CSVCache::from_reader(args, std::io::stdin().lock())?)]
+    } else if args.multi_section {
+        CSVCache::load_sections(args, path, table_name, reborrow_callback(&mut on_progress))?
+            .into_iter()
+            .enumerate()
+            .map(|(ii, (title, cache))| match title {
+                Some(title) => (sanitize_identifier(&title), cache),
+                None => (format!("{}_{}", table_name, ii + 1), cache),
+            })
+            .collect::<Vec<(String, CSVCache)>>()
+    } else {
+        vec![(table_name.to_string(), CSVCache::load_with_progress(args, path, table_name, reborrow_callback(&mut on_progress))?)]
+    };
+
+    let tables = match args.pivot.as_ref() {
+        Some(columns) => tables.into_iter()
+            .map(|(table_name, cache)| Ok((table_name, cache.pivot(columns)?)))
+            .collect::<Result<Vec<(String, CSVCache)>, ImportError>>()?,
+        None => tables,
+    };
+
+    // The schema comes after --pivot, so it can name the attribute columns.
+    let schema = args.column_order_from_header_file.as_deref().map(read_schema_file).transpose()?;
+    let tables = match schema.as_deref() {
+        Some(schema) => tables.into_iter()
+            .map(|(table_name, cache)| Ok((table_name, cache.conform(schema, args.reject_extra_columns)?)))
+            .collect::<Result<Vec<(String, CSVCache)>, ImportError>>()?,
+        None => tables,
+    };
+
+    // stdin can't be read a second time to show the lines around an error.
+    let context = if from_stdin { None } else { ErrorContext::new(args, path) };
+    let mut rows = 0;
+    for (table_name, cached_csv) in &tables {
+        // Let the user know which rows were thrown away as duplicates.
+        if let Some(report) = dedup_report.as_mut() {
+            cached_csv.write_dedup_report(report, &path.display().to_string())?;
+        }
+
+        rows += import_table(conn, args, cached_csv, table_name, schema.as_deref(), context.as_ref(), reborrow_callback(&mut on_progress))?;
+    }
+    finish_import(conn, args, table_name, checksum, warnings_before, rows)
+}
+
+/// Record the file's checksum once its rows are in, and fail the import if it logged warnings and
+/// --warnings-as-errors is set.
+fn finish_import(conn: &Connection, args: &Arguments, table_name: &str, checksum: Option<(String, String)>, warnings_before: u64, rows: u64) -> Result<Option<u64>, ImportError> {
+    if let Some((source, checksum)) = checksum {
+        record_checksum(conn, &source, table_name, &checksum, unix_time(args))?;
+    }
+
+    let warnings = thread_warnings() - warnings_before;
+    if args.warnings_as_errors && warnings > 0 {
+        return Err(ImportError::Other(format!("{} warnings (set RUST_LOG=warn to see them), and --warnings-as-errors is set", warnings)));
+    }
+
+    Ok(Some(rows))
+}
+
+/// The name a source file is recorded under by --verify-checksum.
+/// This is its full path where possible, so it's the same wherever the import is run from.
+fn checksum_source(path: &Path) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// Import a single CSV file into a table, the same way the command line tool imports each of its inputs.
+/// `args` can be built with `Arguments::try_parse_from`, as if they'd been given on the command line.
+/// If there's an `on_progress` callback, it's called every so often while the file is read and its rows are
+/// inserted, and once more at the end of each, so an application can show progress however it likes.
+/// Returns how many rows were imported, or None if the file was skipped because of --skip-if-unchanged.
+pub fn import_csv(conn: &Connection, args: &Arguments, path: &Path, table_name: &str, on_progress: Option<ProgressCallback>) -> Result<Option<u64>, ImportError> {
+    import_file(conn, args, path, table_name, None, on_progress)
+}
+
+/// Create a table from a loaded CSV file and fill it in, returning how many rows went in.
+/// If there's a `schema`, the columns already match it, and it gives the types of some of them.
+/// If there's a `context`, the lines around any row which can't be inserted are shown.
+pub fn import_table(conn: &Connection, args: &Arguments, cached_csv: &CSVCache, table_name: &str, schema: Option<&[SchemaColumn]>, context: Option<&ErrorContext>, on_progress: Option<ProgressCallback>) -> Result<u64, ImportError> {
+    let _table = log_table(table_name);
+
+    // Construct the table info.
+    let header = cached_csv.header();    
+
+    // Run every value through its column's transforms first, so types are inferred from what will be stored.
+    // Short rows are padded out so every column gets a value of its own type.
+    let pipelines = column_pipelines(&header, args)?;
+    let mut rows = cached_csv.rows_iter()
+        .map(|x| (0..header.len().max(x.len()))
+            .map(|ii| {
+                let value = x.get(ii).map_or("", |y| y.as_str());
+                match pipelines.get(ii) {
+                    Some(operations) => apply_operations(operations, value),
+                    None => value.to_string(),
+                }
+            })
+            .collect::<Vec<String>>()
+        ).collect::<Vec<Vec<String>>>();
+
+    // Check the values against any --validate patterns before anything is written.
+    let failures = validate_rows(&header, &rows, cached_csv, args)?;
+    if failures > 0 {
+        warn!("{} values in '{}' failed validation", failures, table_name);
+    }
+
+    let percent_columns = (0..header.len())
+        .filter(|&ii| pipelines[ii].last() == Some(&Operation::Percent))
+        .collect::<Vec<usize>>();
+    for name in &args.text_columns {
+        if !header.contains(&name.as_str()) {
+            return Err(ImportError::Other(format!("Text column '{}' doesn't exist!", name)));
+        }
+    }
+    for name in &args.infer_columns {
+        if !header.contains(&name.as_str()) {
+            return Err(ImportError::Other(format!("Infer column '{}' doesn't exist!", name)));
+        }
+    }
+    for name in &args.keep_text_columns {
+        match header.iter().position(|h| h == name) {
+            None => return Err(ImportError::Other(format!("Keep-text column '{}' doesn't exist!", name))),
+            Some(index) if !pipelines[index].is_empty() => {
+                return Err(ImportError::Other(format!("Column '{}' is kept as text, so it can't be transformed!", name)));
+            },
+            Some(_) => (),
+        }
+    }
+    // Null tokens are left out of type inference, since they'll be stored as NULL whatever the type.
+    let null_tokens = column_null_tokens(&header, args)?;
+    let is_null_token = |ii: usize, value: &str| null_tokens.get(ii).is_some_and(|tokens| tokens.contains(&value));
+
+    // Sensitive values are replaced now, so nothing after this point sees them.
+    let redactions = column_redactions(&header, &pipelines, args)?;
+    for row in &mut rows {
+        for (ii, value) in row.iter_mut().enumerate() {
+            if let Some(mode) = redactions.get(ii).copied().flatten().filter(|_| !is_null_token(ii, value)) {
+                *value = redact(mode, value, args);
+            }
+        }
+    }
+
+    // Date columns are TEXT, but have their values rewritten in ISO form.
+    type ColumnTypes = (Vec<ColumnType>, Vec<Option<DateFormat>>);
+    let ((column_types, date_formats), type_reasons): (ColumnTypes, Vec<String>) = (0..header.len())
+        .map(|ii| if percent_columns.contains(&ii) {
+            ((ColumnType::Real, None), "a percentage column".to_string())
+        } else if let Some(mode) = redactions[ii] {
+            ((ColumnType::Text, None), format!("redacted with --redact {}", mode.as_str()))
+        } else if args.keep_text_columns.iter().any(|name| name == header[ii]) {
+            ((ColumnType::Text, None), "kept as it is with --keep-text".to_string())
+        } else if args.text_columns.iter().any(|name| name == header[ii]) {
+            ((ColumnType::Text, None), "given with --text-columns".to_string())
+        } else if let Some(column_type) = schema.and_then(|schema| schema.get(ii)).and_then(|column| column.column_type) {
+            ((column_type, None), "given in the schema file".to_string())
+        } else if !args.infer_columns.is_empty() && !args.infer_columns.iter().any(|name| name == header[ii]) {
+            ((ColumnType::Text, None), "not one of the --infer-columns".to_string())
+        } else if args.infer_types {
+            let values = rows.iter()
+                .map(|row| row.get(ii).map(|v| v.as_str()).filter(|v| !is_null_token(ii, v)))
+                .collect::<Vec<Option<&str>>>();
+            match infer_type_explained(&values, args) {
+                (ColumnType::Text, Some(_)) if let Some(format) = infer_date_format(&values).filter(|_| args.infer_dates) => {
+                    ((ColumnType::Text, Some(format)), format!("dates in {} format", format.describe()))
+                },
+                (column_type, Some(value)) => ((column_type, None), format!("inferred from '{}'", value)),
+                (column_type, None) => ((column_type, None), "inferred; there are no values".to_string()),
+            }
+        } else {
+            ((ColumnType::Text, None), "--infer-types isn't set".to_string())
+        })
+        .unzip();
+    for (name, format) in header.iter().zip(&date_formats) {
+        if let Some(format) = format {
+            info!("column '{}' holds dates in {} format", name, format.describe());
+        }
+    }
+    // Each column is declared with its type, and its collation if it has one.
+    let collations = column_collations(conn, &header, args)?;
+    let definitions = column_types.iter()
+        .zip(&collations)
+        .map(|(column_type, collation)| column_definition(*column_type, collation.as_deref()))
+        .collect::<Vec<String>>();
+    let generated_definitions = args.generated_columns.iter()
+        .map(|generated| generated.definition())
+        .collect::<Vec<String>>();
+    let table_columns = table_columns(conn, args, &header, &definitions, &generated_definitions)?;
+
+    let columns = header.iter()
+        .zip(&column_types)
+        .zip(type_reasons)
+        .zip(&collations)
+        .map(|(((name, column_type), reason), collation)| ColumnPlan { name, column_type: *column_type, reason, collation: collation.as_deref() })
+        .collect::<Vec<ColumnPlan>>();
+    if args.explain_types {
+        let evidence = (0..header.len())
+            .map(|ii| {
+                let mut evidence = TypeEvidence::default();
+                for (row_number, row) in rows.iter().enumerate() {
+                    let value = row.get(ii)
+                        .map(|value| value.as_str())
+                        .filter(|value| !is_null_token(ii, value) && !cached_csv.is_missing(row_number, ii));
+                    evidence.add(row_number as u64 + 1, value, args);
+                }
+                evidence
+            })
+            .collect::<Vec<TypeEvidence>>();
+        explain_types(args, table_name, &columns, &evidence);
+    }
+
+    if args.dry_run {
+        explain_table(args, cached_csv, table_name, &columns, &pipelines, &rows);
+        return Ok(0);
+    }
+
+    let ImportTarget { upsert, first_rowid } = prepare_table(conn, args, table_name, &header, &column_types, &table_columns)?;
+
+    // Now, iterate through the rows from the CSV file and populate the SQLite table.
+    // Each row is converted to the values to store in its columns, or None if --on-type-error skip leaves it out.
+    let convert_row = |row_number: usize, x: &[String]| -> Result<Option<Vec<Value>>, ImportError> {
+        let mut record = vec![];
+        for (ii, value) in x.iter().enumerate() {
+            let value = value.as_str();
+            let column_type = column_types.get(ii).copied().unwrap_or(ColumnType::Text);
+            let is_percent = percent_columns.contains(&ii) && !value.is_empty();
+            let converted = if is_null_token(ii, value) || cached_csv.is_missing(row_number, ii) {
+                Some(Value::Null)
+            } else if is_percent {
+                parse_percent(value, args).map(Value::Real)
+            } else if let Some(format) = date_formats.get(ii).copied().flatten().filter(|_| !value.is_empty()) {
+                // A value in some other format is kept as it is, since it's still TEXT like the rest of the column.
+                let iso = format.to_iso(value);
+                if iso.is_none() {
+                    let at = RowLocation {
+                        row: Some(row_number as u64 + 1),
+                        line: cached_csv.row_line(row_number),
+                        column: Some(header[ii]),
+                    };
+                    row_warning(args, ErrorCategory::TypeMismatch, at, format_args!(
+                        "row #{}: '{}' in column '{}' isn't a date in {} format, so it was stored as it is",
+                        row_number + 1, value, header[ii], format.describe(),
+                    ));
+                }
+                Some(Value::Text(iso.unwrap_or_else(|| value.to_string())))
+            } else {
+                to_sql_value(value, column_type, args)
+            };
+            if let Some(converted) = converted {
+                record.push(converted);
+                continue;
+            }
+
+            let message = format!(
+                "row #{}: can't store '{}' in column '{}' as {}",
+                row_number + 1, value, header[ii], if is_percent { "a percentage" } else { column_type.as_sql() },
+            );
+            match args.on_type_error {
+                TypeErrorAction::Abort => return Err(ImportError::Other(message)),
+                TypeErrorAction::Null => record.push(Value::Null),
+                TypeErrorAction::Text => record.push(Value::Text(value.to_string())),
+                TypeErrorAction::Skip => (),
+            }
+            let at = RowLocation {
+                row: Some(row_number as u64 + 1),
+                line: cached_csv.row_line(row_number),
+                column: Some(header[ii]),
+            };
+            row_warning(args, ErrorCategory::TypeMismatch, at, format_args!("{}", message));
+            if args.on_type_error == TypeErrorAction::Skip {
+                return Ok(None);
+            }
+        }
+        Ok(Some(record))
+    };
+
+    // Without --pipeline, every row is converted before any are inserted, so --on-type-error abort leaves the table
+    // untouched.
+    let records = if args.pipeline {
+        None
+    } else {
+        let mut records = vec![];
+        for (row_number, x) in rows.iter().enumerate() {
+            if let Some(record) = convert_row(row_number, x)? {
+                records.push((row_number, record));
+            }
+        }
+        Some(records)
+    };
+
+    let total = records.as_ref().map_or(rows.len(), |records| records.len());
+    let mut progress = Progress::new(args, &format!("Importing {}", table_name), Some(total as u64))
+        .with_callback(table_name, ImportStage::Inserting, on_progress);
+    progress.set_bytes(cached_csv.bytes_read());
+    let mut inserted = 0;
+    let mut uncommitted = 0;
+    let mut insert = |ii: usize, row: &[Value]| -> Result<(), ImportError> {
+        match add_row(conn, table_name, &header, row, upsert.as_deref()) {
+            Ok(_) => inserted += 1,
+            Err(er) => {
+                let at = RowLocation { row: Some(ii as u64 + 1), line: cached_csv.row_line(ii), column: None };
+                row_error(args, ErrorCategory::of_insert_error(&er), at, format_args!("error adding row #{}: {}", ii + 1, er));
+                if let (Some(context), Some(line), false) = (context, cached_csv.row_line(ii), args.summary_only) {
+                    context.show(line);
+                }
+            },
+        }
+        progress.tick(1);
+        uncommitted += 1;
+        if args.connection_pool > 1 && uncommitted >= POOL_COMMIT_EVERY {
+            conn.execute_batch(&format!("RELEASE {0}; SAVEPOINT {0};", INSERT_SAVEPOINT))?;
+            uncommitted = 0;
+        }
+        Ok(())
+    };
+
+    // The rows go in inside a savepoint, so they're committed together rather than one at a time. With
+    // --connection-pool, they're committed every POOL_COMMIT_EVERY rows, so the other connections aren't kept
+    // waiting to write for long.
+    conn.execute_batch(&format!("SAVEPOINT {};", INSERT_SAVEPOINT))?;
+    let result = if let Some(records) = records {
+        records.iter().try_for_each(|(ii, row)| insert(*ii, row))
+    } else {
+        // Rows are converted on another thread while this one inserts them, a batch at a time. The channel only
+        // holds a few batches, so conversion can't get far ahead of the inserts.
+        let log_context = log_context();
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<(usize, Vec<Value>)>>(PIPELINE_DEPTH);
+        let (converted, warnings, row_errors, inserting) = std::thread::scope(|scope| {
+            let converter = scope.spawn(|| {
+                let _context = adopt_log_context(log_context);
+                let (warnings, row_errors) = (thread_warnings(), thread_row_errors());
+                let result = (|| -> Result<(), ImportError> {
+                    for (batch_number, batch) in rows.chunks(PIPELINE_BATCH).enumerate() {
+                        let mut records = vec![];
+                        for (ii, x) in batch.iter().enumerate() {
+                            let row_number = batch_number * PIPELINE_BATCH + ii;
+                            if let Some(record) = convert_row(row_number, x)? {
+                                records.push((row_number, record));
+                            }
+                        }
+                        if sender.send(records).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(())
+                })();
+                drop(sender);
+                (result, thread_warnings() - warnings, thread_row_errors().since(&row_errors))
+            });
+            // Once inserting fails, the receiver is dropped, which stops the converter too.
+            let inserting = receiver.into_iter().try_for_each(|records| {
+                records.iter().try_for_each(|(ii, row)| insert(*ii, row))
+            });
+            let (converted, warnings, row_errors) = converter.join().unwrap();
+            (converted, warnings, row_errors, inserting)
+        });
+        // The converter's warnings and errors count towards this file, as if they'd happened on this thread.
+        credit_thread_warnings(warnings);
+        credit_thread_row_errors(row_errors);
+        inserting.and(converted)
+    };
+    // Whatever went in before a failure is kept, just as it would be if each row had been committed on its own.
+    conn.execute_batch(&format!("RELEASE {};", INSERT_SAVEPOINT))?;
+    result?;
+    progress.finish();
+
+    Ok(inserted - filter_rows(conn, args, table_name, first_rowid)?)
+}
+
+/// Put together the columns of the table for an import: the ones from the file with their definitions, then any
+/// --generated-column ones. The generated columns, --primary-key and --index-column are all checked here, before
+/// anything is created.
+pub fn table_columns<'a>(conn: &Connection, args: &'a Arguments, header: &[&'a str], definitions: &'a [String], generated_definitions: &'a [String]) -> Result<Vec<(&'a str, &'a str)>, ImportError> {
+    let mut table_columns = header.iter()
+        .zip(definitions)
+        .map(|(h, d)| (*h, d.as_str()))
+        .collect::<Vec<(&str, &str)>>();
+
+    // Generated columns go after the rest.
+    for generated in &args.generated_columns {
+        if header.contains(&generated.name.as_str()) {
+            return Err(ImportError::Other(format!("Generated column '{}' is already a column!", generated.name)));
+        }
+        check_generated_column(conn, &table_columns, generated)
+            .map_err(|er| ImportError::Other(format!("bad --generated-column '{}': {}", generated.name, er)))?;
+    }
+    for (generated, definition) in args.generated_columns.iter().zip(generated_definitions) {
+        table_columns.push((&generated.name, definition));
+    }
+
+    TableKey::from_args(args).check(header)?;
+
+    // Check index column to make sure it exists.
+    if let Some(column) = args.index_column.as_ref() {
+        if column == "auto" {
+            // Auto mode.
+        }
+        else if !header.contains(&column.as_ref()) {
+            return Err(ImportError::Other(format!("Index column '{}' doesn't exist!", column)));
+        }
+    }
+    Ok(table_columns)
+}
+
+/// What `prepare_table` found out about the table, for inserting the rows.
+pub struct ImportTarget {
+    /// The ON CONFLICT clause for --merge-into.
+    pub upsert: Option<String>,
+
+    /// The rowid of the last row before this import, for --row-filter-sql.
+    pub first_rowid: i64,
+}
+
+/// Get the table for an import ready for its rows: create it if it doesn't exist, and deal with an existing one
+/// as --promote-types, --add-missing-columns and --merge-into say.
+pub fn prepare_table(conn: &Connection, args: &Arguments, table_name: &str, header: &[&str], column_types: &[ColumnType], table_columns: &[(&str, &str)]) -> Result<ImportTarget, ImportError> {
+    // Make the table in the SQLite database.
+    let existing_types = table_column_types(conn, table_name)?;
+
+    let existing_columns = existing_types.iter().map(|(name, _)| name.clone()).collect::<Vec<String>>();
+    if args.merge_into && existing_columns.is_empty() {
+        return Err(ImportError::Other(format!("--merge-into needs table '{}' to exist already!", table_name)));
+    }
+    create_table(conn, table_name, table_columns.to_vec(), TableKey::from_args(args))?;
+
+    // When appending to an existing table, widen any columns which are too narrow for the new rows.
+    if args.promote_types {
+        let promotions = header.iter()
+            .zip(column_types)
+            .filter_map(|(name, &column_type)| {
+                let (_, declared) = existing_types.iter().find(|(existing, _)| existing == name)?;
+                let existing_type = ColumnType::from_sql(declared)?;
+                (column_type > existing_type).then_some((*name, declared.as_str(), column_type.as_sql()))
+            })
+            .collect::<Vec<(&str, &str, &str)>>();
+        for (name, from, to) in &promotions {
+            warn!("promoting column '{}' of table '{}' from {} to {}", name, table_name, from, to);
+        }
+        if !promotions.is_empty() {
+            change_column_types(conn, table_name, &promotions)?;
+        }
+    }
+
+    // When appending to an existing table, bring in any columns it doesn't have yet.
+    if args.add_missing_columns && !existing_columns.is_empty() {
+        for (column_name, column_type) in table_columns {
+            if !existing_columns.iter().any(|c| c == column_name) {
+                info!("adding column '{}' ({}) to table '{}'", column_name, column_type, table_name);
+                add_column(conn, table_name, column_name, column_type)?;
+            }
+        }
+    }
+
+    // Rows are matched up with the ones already in the table by its key.
+    let upsert = if args.merge_into {
+        let key = table_primary_key(conn, table_name)?;
+        if key.is_empty() {
+            return Err(ImportError::Other(format!("Table '{}' has no primary key to merge on!", table_name)));
+        }
+        if let Some(name) = key.iter().find(|name| !header.contains(&name.as_str())) {
+            return Err(ImportError::Other(format!("Primary key column '{}' of table '{}' isn't in the file, so rows can't be matched up!", name, table_name)));
+        }
+        let columns = table_column_names(conn, table_name)?;
+        if let Some(name) = header.iter().find(|name| !columns.iter().any(|c| c == *name)) {
+            return Err(ImportError::Other(format!("Column '{}' isn't in table '{}'; use --add-missing-columns to add it", name, table_name)));
+        }
+        Some(build_upsert_clause(&key, header))
+    } else {
+        None
+    };
+
+
+    // Rows are filtered once they're in, so only the ones this import adds should be looked at.
+    let first_rowid = match args.row_filter_sql.as_deref() {
+        Some(filter) => {
+            check_row_filter(conn, table_name, filter)
+                .map_err(|er| ImportError::Other(format!("bad --row-filter-sql '{}': {}", filter, er)))?;
+            max_rowid(conn, table_name)?
+        },
+        None => 0,
+    };
+
+    Ok(ImportTarget { upsert, first_rowid })
+}
+
+/// Remove the rows this import added which don't match --row-filter-sql, returning how many there were.
+pub fn filter_rows(conn: &Connection, args: &Arguments, table_name: &str, first_rowid: i64) -> Result<u64, ImportError> {
+    let Some(filter) = args.row_filter_sql.as_deref() else {
+        return Ok(0);
+    };
+    let deleted = delete_unmatched_rows(conn, table_name, filter, first_rowid)?;
+    info!("{} rows in '{}' didn't match --row-filter-sql and were removed", deleted, table_name);
+    Ok(deleted as u64)
+}
@@ -0,0 +1,689 @@
+//! Import CSV files into SQLite databases.
+//! The command line tool is a thin wrapper around [`run`], and [`import_csv`] imports a single file for programs
+//! which embed this crate.
+
+use std::{
+    fs::File,
+    io::Write,
+    path::{PathBuf, Path},
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+
+use log::{debug, error, info, warn};
+
+use clap::Parser;
+use rusqlite::{
+    Connection,
+    OpenFlags,
+    Result,
+    types::Value,
+};
+
+// Crate modules
+pub mod sql;
+use sql::*;
+
+pub mod csvcache;
+pub use csvcache::*;
+
+pub mod error;
+pub use error::*;
+
+pub mod types;
+use types::*;
+
+pub mod dates;
+use dates::*;
+
+pub mod progress;
+pub use progress::*;
+
+pub mod transform;
+use transform::*;
+
+pub mod context;
+use context::*;
+
+pub mod validate;
+use validate::*;
+
+pub mod pool;
+use pool::*;
+
+pub mod summary;
+pub use summary::*;
+
+pub mod benchmark;
+use benchmark::*;
+
+pub mod explain;
+use explain::*;
+
+pub mod logging;
+pub use logging::*;
+
+pub mod repl;
+use repl::*;
+
+pub mod checksum;
+use checksum::*;
+
+pub mod widths;
+use widths::*;
+
+pub mod sniff;
+pub use sniff::*;
+
+pub mod runs;
+use runs::*;
+
+pub mod redact;
+use redact::*;
+
+pub mod schema;
+use schema::*;
+
+pub mod streaming;
+pub use streaming::*;
+
+pub mod sample;
+use sample::*;
+
+pub mod import;
+pub use import::import_csv;
+use import::*;
+
+pub mod cli;
+pub use cli::run;
+use cli::*;
+
+// Command line arguments.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Arguments {
+    /// CSV files to operate on.
+    /// Each file is imported as its own table.
+    /// Use - for stdin, which is imported into a table called "stdin" unless it's given a name.
+    #[arg(required_unless_present_any = ["input_list", "benchmark"])]
+    input: Vec<String>,
+
+    /// File listing the CSV files to operate on, one per line.
+    /// A line may give a table name for its file after a tab.
+    #[arg(long)]
+    input_list: Option<String>,
+
+    /// Path to the output file.
+    /// Use - for stdout; logging and any other messages always go to stderr, so the output stays clean.
+    /// Default: use the first input path, with .csv replaced by .db (or .sql for --output-format sql).
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Read the files and work out what would be imported, without writing anything.
+    /// Each table is listed with its row and column counts.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// With --dry-run, show everything that would be done for each table: the type of each column and the values
+    /// which decided it, header names which were rewritten or repeated, the transforms, and the exact CREATE TABLE
+    /// and INSERT statements.
+    #[arg(long, requires = "dry_run")]
+    explain: bool,
+
+    /// Show the evidence behind each column's type: how many of its values are integers, reals, text and NULL, and
+    /// the first value which made it wider (say, the first real in a column of integers). Values count as the
+    /// narrowest type which can hold them. Works with or without --dry-run.
+    #[arg(long, conflicts_with = "streaming")]
+    explain_types: bool,
+
+    /// Write each input file to a database of its own in this directory, named after its table, instead of
+    /// putting everything in one database. A master.sql script is written alongside them which ATTACHes every
+    /// database (and creates any --create-view views), so the tables can still be queried together; open it from
+    /// the directory with `sqlite3 -init master.sql`. Each database can then be refreshed on its own.
+    #[arg(long, conflicts_with_all = ["output", "output_format", "connection_pool", "dry_run"])]
+    separate_databases: Option<String>,
+
+    /// How to write log messages. json writes one object per line, with the file, table, row, line and column each
+    /// message is about as fields of their own. Set RUST_LOG to choose how much is logged.
+    #[arg(long, value_enum)]
+    #[arg(default_value = "text")]
+    log_format: LogFormat,
+
+    /// Once everything is imported, read SQL statements from stdin and print what they return, to look over or tidy
+    /// up the tables. Changes are made in a transaction: use .commit to save them, and see --repl-commit-on-exit.
+    #[arg(long, conflicts_with_all = ["dry_run", "separate_databases"])]
+    repl: bool,
+
+    /// What to do with uncommitted REPL changes on exit.
+    #[arg(long, value_enum)]
+    #[arg(default_value = "ask")]
+    repl_commit_on_exit: CommitOnExit,
+
+    /// Make the output database as small as possible, for sending to other people. The database uses small pages,
+    /// and is VACUUMed once everything is imported. Small pages make queries on big tables a little slower.
+    #[arg(long)]
+    compact: bool,
+
+    /// What kind of file to write.
+    #[arg(long, value_enum)]
+    #[arg(default_value = "sqlite")]
+    output_format: OutputFormat,
+
+    /// Append rows to an existing SQLite database.
+    /// The header row must match if this is set.
+    #[arg(short, long)]
+    #[arg(default_value = "false")]
+    append: bool,
+
+    /// Use a specific column as an index.
+    /// If this is set to "auto", a new column called "id" will be created with the value being the row number of the CSV file.
+    /// If set to blank (""), there will be no index column.
+    /// Default: "auto"
+    /// [NOT IMPLEMENTED]
+    #[arg(short, long)]
+    #[arg(default_value = "auto")]
+    index_column: Option<String>,
+
+    /// Make this column the table's primary key, instead of adding an "id" column. Given more than once, the key is
+    /// made of all of them, in order. Rows whose key is already in the table fail to insert, like any other bad row.
+    #[arg(long)]
+    primary_key: Vec<String>,
+
+    /// Store the table WITHOUT ROWID, clustered by its --primary-key, which is needed for this. For natural text or
+    /// composite keys this is usually smaller and faster to look up by key, since there's no separate index on the
+    /// key. It's slower when rows are large (more than a few hundred bytes each), key columns can't be NULL, and the
+    /// table has no rowid, so --row-filter-sql can't be used. Tables which already exist are left as they are.
+    #[arg(long, requires = "primary_key", conflicts_with = "row_filter_sql")]
+    without_rowid: bool,
+
+    /// Don't use the first row as the header.
+    #[arg(long = "disable-header")]
+    #[arg(default_value = "true")]
+    #[arg(action = clap::ArgAction::SetFalse)]
+    use_header: bool,
+
+    /// What the input files are. By default this is worked out from what's in each one, rather than from its name.
+    #[arg(long, value_enum, default_value = "auto")]
+    input_format: InputFormat,
+
+    /// Delimiter. If this isn't given, it's ',' for --input-format csv and a tab for tsv, and is worked out from
+    /// each file with auto.
+    #[arg(long, short = 'd')]
+    delimiter: Option<char>,
+
+    /// Table name.
+    /// If this is not specified, the table name will be constructed from the CSV file name.
+    /// With several input files, all of them go into this table unless the input list names another.
+    #[arg(long, short = 't')]
+    table_name: Option<String>,
+
+    /// Build each table name from a pattern, for importing many files at once.
+    /// The tokens {basename}, {parent}, {index} (1-based position in the input list) and {date} (YYYYMMDD) are substituted per file.
+    /// The result is made into a valid identifier by replacing anything other than letters, digits and underscores.
+    #[arg(long, conflicts_with = "table_name")]
+    table_name_pattern: Option<String>,
+
+    /// Default column name.
+    /// Columns with no other name specified will be called <default-column-name><column number>, with a 1-indexed column number. For example, the 20th column will be called "column20" by default.
+    /// If --use-header=true and any row has more columns than the header, this is used for the following column, with the 
+    /// If headers are disabled, this will be used for all columns.
+    /// Default: "column"
+    #[arg(long)]
+    #[arg(default_value = "column")]
+    default_column_name: String,
+
+    /// Strip control characters and zero-width characters (such as zero-width spaces) from header names.
+    /// These often come along when headers are copied from web pages, and make names which look right but don't match.
+    /// A warning is logged for each name which changes.
+    #[arg(long)]
+    clean_headers: bool,
+
+    /// Take the header from the first line of the file when it's a comment, like "# name, city, zip".
+    /// The line is split on the delimiter as usual, and surrounding spaces are trimmed from each name.
+    /// If the first line isn't a comment, it's used as the header in the normal way.
+    #[arg(long, conflicts_with = "use_header")]
+    header_from_comment: bool,
+
+    /// Remove this prefix from every header name that has it, e.g. "orders." from "orders.id".
+    #[arg(long)]
+    header_strip_prefix: Option<String>,
+
+    /// Remove this suffix from every header name that has it.
+    #[arg(long)]
+    header_strip_suffix: Option<String>,
+
+    /// Replace every FROM with TO in header names, given as FROM:TO. TO may be empty.
+    /// Replacements run after the prefix and suffix are stripped, in the order given. May be given more than once.
+    #[arg(long = "header-replace")]
+    header_replacements: Vec<HeaderReplacement>,
+
+    /// Drop data rows which are exactly the same as the header, as happens when files are concatenated.
+    /// Each one is logged with a warning, followed by how many there were. These rows don't count towards --rows.
+    #[arg(long)]
+    skip_repeated_headers: bool,
+
+    /// Drop rows which duplicate an earlier row.
+    /// Rows are compared on the --dedup-key columns, or on the whole row if no key is given.
+    #[arg(long)]
+    dedup: bool,
+
+    /// Column to compare rows on when using --dedup.
+    /// May be given more than once to build a composite key.
+    #[arg(long, requires = "dedup")]
+    dedup_key: Vec<String>,
+
+    /// Write the source line number and key of every row dropped by --dedup to this file.
+    #[arg(long, requires = "dedup")]
+    dedup_report: Option<String>,
+
+    /// Guess column types from their contents rather than using TEXT for everything.
+    /// A column is INTEGER or REAL if every non-empty value parses as one; empty values are stored as NULL.
+    #[arg(long)]
+    infer_types: bool,
+
+    /// Only infer the types of these columns, leaving the rest as TEXT without looking at them.
+    /// This saves time on wide files where only a few columns are numeric. Takes a comma-separated list, and may be
+    /// given more than once.
+    #[arg(long, requires = "infer_types", value_delimiter = ',')]
+    infer_columns: Vec<String>,
+
+    /// Treat scientific-notation values which are whole numbers, like "1.23E+15", as integers.
+    /// This is exact for anything in i64 range, but can't recover digits the exporting program already rounded away.
+    #[arg(long, requires = "infer_types")]
+    coerce_scientific: bool,
+
+    /// Detect columns holding dates, like "2024-03-01" or "03/01/2024", and store them as TEXT in ISO form.
+    /// A column holds dates if every value is one; values not in the format most of them use are stored as they are,
+    /// with a warning.
+    #[arg(long, requires = "infer_types")]
+    infer_dates: bool,
+
+    /// Strip currency symbols and grouping commas from numbers, so "$1,234.56" is stored as the REAL 1234.56.
+    /// This only applies to columns inferred as numeric, so text columns are never changed.
+    #[arg(long, requires = "infer_types")]
+    strip_currency: bool,
+
+    /// The currency symbols removed by --strip-currency.
+    #[arg(long, requires = "strip_currency")]
+    #[arg(default_value = "$€£¥")]
+    currency_symbols: String,
+
+    /// Column holding percentages like "12.5%", to be stored as the REAL fraction 0.125.
+    /// May be given more than once. Values which can't be read as a percentage are handled by --on-type-error.
+    #[arg(long = "percent-column")]
+    percent_columns: Vec<String>,
+
+    /// Column to store as TEXT whatever --infer-types makes of it. May be given more than once.
+    #[arg(long = "text-columns")]
+    text_columns: Vec<String>,
+
+    /// Column to store exactly as it is in the CSV file: it's always TEXT, and isn't touched by any inference or
+    /// coercion. Naming it in a transform option as well is an error. May be given more than once.
+    #[arg(long = "keep-text")]
+    keep_text_columns: Vec<String>,
+
+    /// With --infer-types, a column of whole numbers with more digits than this is kept as TEXT, as are columns
+    /// with leading zeros. These are usually codes (phone numbers, account numbers, zip codes) rather than numbers.
+    #[arg(long)]
+    #[arg(default_value = "15")]
+    max_integer_digits: usize,
+
+    /// The largest field to read from a CSV file, in bytes.
+    /// An unclosed quote can make the rest of the file look like one giant field. Rather than running out of memory,
+    /// the import stops with an error once the field being read gets this big. Any quotes around it count too.
+    #[arg(long)]
+    #[arg(default_value = "134217728")]
+    max_field_size: u64,
+
+    /// Fail if the header (or the first row, with --disable-header) has fewer than this many columns.
+    /// Too few columns usually means the delimiter is wrong, or the file was cut off.
+    #[arg(long)]
+    min_columns: Option<usize>,
+
+    /// Fail if the header (or the first row, with --disable-header) has more than this many columns.
+    /// Too many usually means the delimiter is wrong, or the file isn't CSV at all.
+    #[arg(long)]
+    max_columns: Option<usize>,
+
+    /// Show progress on stderr while reading and importing each file.
+    #[arg(long)]
+    progress: bool,
+
+    /// Log a progress line (at info level) every this many rows.
+    /// This is for logging to a file, where a live --progress line is no use. Set to 0 to turn it off.
+    #[arg(long)]
+    #[arg(default_value = "100000")]
+    progress_interval: u64,
+
+    /// Count the lines in each file before reading it, so --progress can show a percentage.
+    /// This is only an estimate: it over-counts when quoted fields contain newlines.
+    #[arg(long, requires = "progress")]
+    estimate_rows: bool,
+
+    /// Read files strictly by RFC 4180, stopping with the line number of anything that doesn't conform.
+    /// Records must end with CRLF, quotes may only surround whole fields, every record needs the same number of
+    /// fields, and there are no comment lines. Handy for checking that a file follows the standard.
+    #[arg(long, conflicts_with = "multi_section")]
+    rfc4180: bool,
+
+    /// Check that every value in a column matches a regex, given as COLUMN:REGEX, e.g. "zip:^[0-9]{5}$".
+    /// The regex can match anywhere in the value unless it's anchored with ^ and $. Empty values aren't checked.
+    /// Values which don't match are reported with their row number. May be given more than once.
+    #[arg(long = "validate")]
+    validations: Vec<Validation>,
+
+    /// Only keep the rows for which this SQL expression is true, like "price > 100 AND region IN ('NA', 'EU')".
+    /// It can use anything SQLite can, with the table's columns (after any transforms and type inference) by name.
+    /// Every row is inserted first and the ones which don't match are deleted afterwards, so the database briefly
+    /// holds the whole file, the ids have gaps, and the space the deleted rows used isn't given back until a VACUUM.
+    /// Rows already in the table when appending aren't touched.
+    #[arg(long)]
+    row_filter_sql: Option<String>,
+
+    /// Stop importing a file at the first value which fails --validate, rather than just warning about it.
+    #[arg(long)]
+    strict: bool,
+
+    /// Treat warnings as errors: a file fails to import if anything is warned about while it's imported (like a
+    /// rewritten header, a dropped repeat of the header, or a value which can't be stored as its column's type), and
+    /// the exit code is non-zero if anything was warned about at all. The file's rows are still imported.
+    /// Handy in CI, to make sure imports are clean. Warnings are counted whatever RUST_LOG is set to.
+    #[arg(long)]
+    warnings_as_errors: bool,
+
+    /// Record how each file's import went in an _import_runs table in the output database: its status (imported,
+    /// skipped or failed), how many rows went in, how many row errors and warnings there were, and why it failed.
+    /// Rows from earlier runs are kept, so after a long batch job `SELECT * FROM _import_runs WHERE status = 'failed'`
+    /// shows what needs another look.
+    #[arg(long, conflicts_with = "separate_databases")]
+    summary_table: bool,
+
+    /// Convert rows to the values to store on a second thread while the first inserts them, so the work of parsing
+    /// numbers, dates and so on overlaps with SQLite's writes. All the inserts still happen on one connection.
+    /// Only a few batches of rows are held between the two, to limit the memory used. With --on-type-error abort,
+    /// the rows before the bad value will already have been inserted.
+    #[arg(long)]
+    pipeline: bool,
+
+    /// Read each file a record at a time and insert the rows in batches, rather than reading the whole file into
+    /// memory first, so files of any size can be imported. The file is read twice, once to check the values and infer
+    /// the column types and once to insert the rows, so it can't come from stdin. Rows go in inside a transaction
+    /// which is committed every --commit-every rows; if the import fails, the rows since the last commit are rolled
+    /// back. Rows are transformed, validated and redacted as usual, but the options which need every row at once
+    /// (--multi-section, --pivot, --dedup, --infer-dates and --explain) can't be used with this, and nor can
+    /// --pipeline, which is another way of overlapping the reading with the inserts.
+    #[arg(long, conflicts_with_all = ["multi_section", "pivot", "dedup", "infer_dates", "explain", "pipeline"])]
+    streaming: bool,
+
+    /// How many rows --streaming buffers before inserting them with a single INSERT.
+    #[arg(long)]
+    #[arg(default_value = "1000")]
+    batch_size: usize,
+
+    /// How many rows --streaming inserts between commits. With --connection-pool, rows are committed at least every
+    /// 1000 rows, whatever this is. Without --streaming, each table's rows are committed together.
+    #[arg(long)]
+    #[arg(default_value = "100000")]
+    commit_every: u64,
+
+    /// When a row can't be read or inserted, show this many lines of the file either side of it.
+    #[arg(long)]
+    #[arg(default_value = "0")]
+    error_context: usize,
+
+    /// A value which means NULL, like "NA" or "-", in every column. It's stored as NULL whatever the column's type,
+    /// and isn't counted when inferring types. --keep-text columns keep it as it is.
+    #[arg(long)]
+    null_token: Option<String>,
+
+    /// A value which means NULL in one column only, given as COLUMN:TOKEN, e.g. "count:-1". This is on top of any
+    /// --null-token. May be given more than once, including for the same column.
+    #[arg(long = "null-token-column")]
+    null_token_columns: Vec<NullToken>,
+
+    /// What to do with a value which can't be stored as its column's type, like "abc" in an INTEGER column.
+    #[arg(long, value_enum)]
+    #[arg(default_value = "text")]
+    on_type_error: TypeErrorAction,
+
+    /// Don't log each row which has a problem; just count them, and report the counts by kind at the end.
+    /// The kinds are parse errors, type mismatches, validation failures, constraint violations and other insert errors.
+    #[arg(long)]
+    summary_only: bool,
+
+    /// Import each blank-line separated section of a file as a table of its own.
+    /// Each section has its own header. A section can start with a title line (one with no delimiters in it) to name
+    /// its table; otherwise the table is named after the file, with the section number on the end.
+    #[arg(long)]
+    multi_section: bool,
+
+    /// Only import the data rows numbered A to B (inclusive, starting from 1), given as A:B.
+    /// Either end can be left off, so "1000:" imports from row 1000 to the end. The header is always read.
+    /// Rows are numbered as they're read, so comment lines, blank lines and the header don't count (and rows dropped
+    /// by --dedup still do). With --multi-section, each section is numbered separately.
+    /// Handy for splitting one big file across several imports.
+    #[arg(long)]
+    rows: Option<RowRange>,
+
+    /// Import a random sample of this many rows, each row being as likely to be picked as any other. The rows are
+    /// picked as the file is read, so only the sample is held in memory, and they're imported in the order they
+    /// appear in the file. This happens after --rows and --dedup; with --multi-section, each section is sampled.
+    #[arg(long, conflicts_with = "streaming")]
+    sample_rows: Option<usize>,
+
+    /// The seed for --sample-rows, so the same rows are picked every time. Without one, a seed is made up and
+    /// logged at the info level.
+    #[arg(long, requires = "sample_rows")]
+    seed: Option<u64>,
+
+    /// Reshape a "long" file with ENTITY,ATTRIBUTE,VALUE columns into a wide table, with a row for each entity and
+    /// a column for each distinct attribute. Attributes an entity has no value for are NULL.
+    /// The whole file is already held in memory, and the pivot makes a second copy with a cell for every entity and
+    /// attribute, so a very sparse file can take much more memory once it's pivoted.
+    #[arg(long)]
+    pivot: Option<PivotColumns>,
+
+    /// A schema file giving the columns of the table, in order, one per line as NAME or NAME:TYPE (INTEGER, REAL or
+    /// TEXT). The file's columns are matched to the schema's by name and put in its order; ones which aren't in the
+    /// schema are dropped, and schema columns which aren't in the file are NULL. A column with a type is stored as
+    /// that type, with values which don't fit handled by --on-type-error, unless it's one of the --keep-text columns.
+    /// Blank lines and lines starting with '#' are ignored.
+    #[arg(long)]
+    column_order_from_header_file: Option<PathBuf>,
+
+    /// With --column-order-from-header-file, fail if the file has columns which aren't in the schema, rather than
+    /// dropping them.
+    #[arg(long, requires = "column_order_from_header_file")]
+    reject_extra_columns: bool,
+
+    /// Record a SHA-256 checksum of each source file in the _import_metadata table, along with the table it went into
+    /// and when. The file is read an extra time to work this out. Files read from stdin aren't recorded.
+    #[arg(long)]
+    verify_checksum: bool,
+
+    /// Skip any file whose checksum matches the one recorded the last time it was imported into the same table, so
+    /// re-running over a folder only imports the files which changed. Implies --verify-checksum.
+    #[arg(long)]
+    skip_if_unchanged: bool,
+
+    /// Create a view once every file has been imported, given as "NAME AS SELECT ...".
+    /// This is handy for joining the tables from a multi-file import. The SELECT is checked before the view is
+    /// created, so a misspelt table or column is an error. May be given more than once.
+    #[arg(long = "create-view")]
+    create_views: Vec<ViewDefinition>,
+
+    /// When appending to a table which is missing some of the CSV file's columns, add them with ALTER TABLE.
+    /// Rows already in the table get NULL for the new columns.
+    #[arg(long)]
+    add_missing_columns: bool,
+
+    /// Merge the rows into a table which already exists, matching them up by its primary key: rows whose key is
+    /// already there have their other columns updated with the file's values, and the rest are inserted. This is an
+    /// UPSERT (INSERT ... ON CONFLICT DO UPDATE), so it happens in one pass. Every column of the key has to be in
+    /// the file, and so does every column of the file in the table, unless --add-missing-columns is set. Columns of
+    /// the table which aren't in the file keep their values. If a key appears more than once, the last row wins.
+    #[arg(long, conflicts_with_all = ["primary_key", "row_filter_sql"])]
+    merge_into: bool,
+
+    /// When appending to a table whose columns are narrower than the CSV file's, widen them (INTEGER to REAL to TEXT)
+    /// before the rows go in, so a REAL value never ends up in an INTEGER column. The table is rebuilt to do this,
+    /// since SQLite can't change a column's type, so this can take a while for a big table.
+    #[arg(long)]
+    promote_types: bool,
+
+    /// Give a column a collation, as COLUMN:COLLATION, so comparisons and sorting on it use that collation without
+    /// needing a COLLATE in every query. SQLite has BINARY (the default), NOCASE (which ignores the case of ASCII
+    /// letters) and RTRIM (which ignores trailing spaces). May be given more than once.
+    #[arg(long = "collate")]
+    collations: Vec<ColumnCollation>,
+
+    /// Add a column computed from the others, given as "NAME = EXPR", like "full_name = first || ' ' || last".
+    /// The expression is SQL, and is worked out whenever the column is read; end it with STORED to work it out once
+    /// when each row goes in instead. May be given more than once.
+    #[arg(long = "generated-column")]
+    generated_columns: Vec<GeneratedColumn>,
+
+    /// Column whose values should be lowercased, so that "USA", "usa" and "Usa" are all stored as "usa".
+    /// May be given more than once.
+    #[arg(long = "lowercase-column")]
+    lowercase_columns: Vec<String>,
+
+    /// Column whose values contain HTML, to have their tags removed and entities decoded, so that
+    /// "<b>Fish &amp; chips</b>" is stored as "Fish & chips". May be given more than once.
+    #[arg(long = "strip-html")]
+    strip_html_columns: Vec<String>,
+
+    /// A pipeline of operations to run a column's values through, like "name: trim | lower | replace(a, b)".
+    /// The operations are trim, lower, upper, replace(from, to), strip_html and percent (which must come last);
+    /// replace arguments may be single-quoted. Operations run left to right, with pipelines for the same column run
+    /// in the order given. --strip-html and --lowercase-column run before any pipelines, and --percent-column after them.
+    /// May be given more than once.
+    #[arg(long = "transform")]
+    transforms: Vec<Transform>,
+
+    /// Keep a sensitive column's values out of the database, as COLUMN:mask or COLUMN:hash. mask stores "***" in
+    /// place of every value; hash stores the HMAC-SHA-256 of the value keyed with --redact-salt, as hex, so equal
+    /// values can still be joined and counted. Empty values and null tokens are left alone, and redacted columns are
+    /// always TEXT. Values are redacted after transforms and --validate, and in warnings and the --dedup-report too.
+    /// --error-context only gives line numbers, since the lines could hold them. May be given more than once.
+    #[arg(long = "redact")]
+    redactions: Vec<ColumnRedaction>,
+
+    /// The salt for --redact COLUMN:hash. Without one, anyone can find a common value by hashing guesses at it,
+    /// so use a long random salt and keep it secret.
+    #[arg(long)]
+    redact_salt: Option<String>,
+
+    /// Stop at the first input file that fails to import.
+    /// Otherwise the remaining files are still imported, and the failures are reported at the end.
+    /// Either way, the exit code is non-zero if any file failed.
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Import up to this many files at once, each on its own thread with a connection from a pool.
+    /// The database is put in WAL mode while importing. SQLite only allows one writer at a time, so inserts still
+    /// happen one after another; what overlaps is reading and parsing the files. Each table's rows are committed
+    /// at least every 1000 rows rather than all at once, so the other connections aren't kept waiting to write for long.
+    /// Only used for SQLite output.
+    #[arg(long, conflicts_with = "dedup_report")]
+    #[arg(default_value = "1")]
+    connection_pool: usize,
+
+    /// How long a connection waits for another to finish writing before giving up, in milliseconds.
+    #[arg(long)]
+    #[arg(default_value = "5000")]
+    busy_timeout: u64,
+
+    /// How to open the output database.
+    #[arg(long, value_enum)]
+    #[arg(default_value = "create")]
+    open_mode: OpenMode,
+
+    /// Pin the clock to the Unix epoch so the output is the same on every run.
+    /// This is for tests which compare output databases.
+    #[arg(long, hide = true, env = "CSV_TO_SQLITE_DETERMINISTIC")]
+    deterministic: bool,
+
+    /// Instead of importing anything, report how wide the fields in each column of a fixed-width file are.
+    /// Columns are found from the character positions which are blank on every line, and the report ends with the
+    /// width of each column.
+    #[arg(long)]
+    column_width_report: bool,
+
+    /// Instead of importing anything, time importing a generated file with this many rows and columns.
+    /// The other options still apply, so this can be used to see what they cost.
+    #[arg(long, hide = true, num_args = 2, value_names = ["ROWS", "COLS"])]
+    benchmark: Option<Vec<usize>>,
+}
+
+/// How many rows are converted at a time with --pipeline.
+const PIPELINE_BATCH: usize = 1000;
+
+/// How many batches of converted rows --pipeline lets wait to be inserted.
+const PIPELINE_DEPTH: usize = 4;
+
+/// The page size used with --compact.
+/// SQLite's default is 4096; smaller pages waste less space at the end of each one.
+const COMPACT_PAGE_SIZE: u32 = 1024;
+
+/// The savepoint each table's rows are inserted in, without --streaming.
+const INSERT_SAVEPOINT: &str = "import_table";
+
+/// How many rows go in between commits with --connection-pool. SQLite only lets one connection write at a time, so
+/// the others have to wait for each commit.
+const POOL_COMMIT_EVERY: u64 = 1000;
+
+/// The input path which means stdin.
+const STDIN: &str = "-";
+
+/// Kinds of output file.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A SQLite database.
+    Sqlite,
+    /// A SQL script of CREATE TABLE and INSERT statements, which can be loaded into another database.
+    Sql,
+}
+
+/// Ways of opening the output database.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpenMode {
+    /// Open the database for writing, creating it if it doesn't exist.
+    Create,
+    /// Open an existing database for writing, failing if it doesn't exist.
+    Existing,
+    /// Create a new database, failing if the file already exists.
+    New,
+    /// Open an existing database without allowing any changes to it.
+    /// Imports will fail in this mode, so nothing can be modified by accident.
+    ReadOnly,
+}
+
+/// What to do with a value which can't be stored as its column's type.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypeErrorAction {
+    /// Store NULL instead.
+    Null,
+    /// Store the value as text anyway; SQLite allows this even in a numeric column.
+    Text,
+    /// Leave the whole row out.
+    Skip,
+    /// Fail the import, before any of its rows are inserted.
+    Abort,
+}
+
+impl TypeErrorAction {
+    /// What happened to the values, for the --summary-only report.
+    pub fn effect(&self) -> &'static str {
+        match self {
+            TypeErrorAction::Null => "stored as NULL",
+            TypeErrorAction::Text => "stored as text",
+            TypeErrorAction::Skip => "their rows were skipped",
+            TypeErrorAction::Abort => "the import was aborted",
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use crate::*;
+use std::{
+    cell::{Cell, RefCell},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// How log messages are written to stderr.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// env_logger's usual human-readable lines.
+    Text,
+    /// One JSON object per line, with the file, table, row, line and column the message is about as fields.
+    Json,
+}
+
+/// What a log message is about, beyond its text.
+/// The file and table are set for everything logged while a file is imported; the rest only for row errors.
+#[derive(Debug, Clone, Default)]
+pub struct LogContext {
+    pub file: Option<String>,
+    pub table: Option<String>,
+    pub row: Option<u64>,
+    pub line: Option<u64>,
+    pub column: Option<String>,
+}
+
+thread_local! {
+    // Each thread has its own, so files imported with --connection-pool don't get each other's context.
+    static CONTEXT: RefCell<LogContext> = RefCell::new(LogContext::default());
+
+    // Likewise, so each file's warnings can be counted for --warnings-as-errors.
+    static THREAD_WARNINGS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// How many warnings there have been on every thread.
+static TOTAL_WARNINGS: AtomicU64 = AtomicU64::new(0);
+
+/// Count warnings which happened on another thread as if they were on this one.
+/// They've already been counted in the total.
+pub fn credit_thread_warnings(count: u64) {
+    THREAD_WARNINGS.with(|warnings| warnings.set(warnings.get() + count));
+}
+
+/// Count a warning for --warnings-as-errors.
+/// Anything logged with `warn!` is counted by the logger, so this is only needed for warnings which aren't logged.
+pub fn count_warning() {
+    THREAD_WARNINGS.with(|count| count.set(count.get() + 1));
+    TOTAL_WARNINGS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// How many warnings there have been on this thread.
+pub fn thread_warnings() -> u64 {
+    THREAD_WARNINGS.with(|count| count.get())
+}
+
+/// How many warnings there have been altogether.
+pub fn total_warnings() -> u64 {
+    TOTAL_WARNINGS.load(Ordering::Relaxed)
+}
+
+/// Wraps env_logger to count warnings, including ones it doesn't show.
+struct CountingLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for CountingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if record.level() == log::Level::Warn {
+            count_warning();
+        }
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Puts the log context back how it was when dropped.
+pub struct ContextGuard {
+    previous: LogContext,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.previous);
+        CONTEXT.with(|context| *context.borrow_mut() = previous);
+    }
+}
+
+/// Change the log context until the guard is dropped.
+fn set_context(change: impl FnOnce(&mut LogContext)) -> ContextGuard {
+    CONTEXT.with(|context| {
+        let previous = context.borrow().clone();
+        change(&mut context.borrow_mut());
+        ContextGuard { previous }
+    })
+}
+
+/// The log context on this thread, to hand to `adopt_log_context` on another.
+pub fn log_context() -> LogContext {
+    CONTEXT.with(|context| context.borrow().clone())
+}
+
+/// Use another thread's log context until the guard is dropped, for work handed off to this thread.
+pub fn adopt_log_context(adopted: LogContext) -> ContextGuard {
+    set_context(|context| *context = adopted)
+}
+
+/// Attribute messages to a file until the guard is dropped.
+pub fn log_file(path: &Path) -> ContextGuard {
+    let file = path.display().to_string();
+    set_context(|context| context.file = Some(file))
+}
+
+/// Attribute messages to a table until the guard is dropped.
+pub fn log_table(table: &str) -> ContextGuard {
+    set_context(|context| context.table = Some(table.to_string()))
+}
+
+/// Where in a file a row error is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RowLocation<'a> {
+    /// The 1-based data row.
+    pub row: Option<u64>,
+
+    /// The line of the file.
+    pub line: Option<u64>,
+
+    pub column: Option<&'a str>,
+}
+
+/// Attribute messages to a place in the file until the guard is dropped.
+pub fn log_location(at: RowLocation) -> ContextGuard {
+    set_context(|context| {
+        context.row = at.row;
+        context.line = at.line;
+        context.column = at.column.map(|column| column.to_string());
+    })
+}
+
+/// Set up logging to stderr in the --log-format, at the level given by RUST_LOG (errors only by default).
+/// Logging always goes to stderr, so it never ends up mixed into output written to stdout.
+/// With --warnings-as-errors, warnings are counted even if they aren't shown.
+pub fn init_logging(args: &Arguments) {
+    let mut builder = env_logger::Builder::from_default_env();
+    builder.target(env_logger::Target::Stderr);
+    if args.log_format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let context = CONTEXT.with(|context| context.borrow().clone());
+            let mut line = format!(
+                r#"{{"time":{},"level":{},"target":{},"message":{}"#,
+                json_string(&buf.timestamp().to_string()),
+                json_string(record.level().as_str()),
+                json_string(record.target()),
+                json_string(&record.args().to_string()),
+            );
+            let strings = [("file", &context.file), ("table", &context.table), ("column", &context.column)];
+            for (name, value) in strings {
+                if let Some(value) = value {
+                    line.push_str(&format!(r#","{}":{}"#, name, json_string(value)));
+                }
+            }
+            for (name, value) in [("row", context.row), ("line", context.line)] {
+                if let Some(value) = value {
+                    line.push_str(&format!(r#","{}":{}"#, name, value));
+                }
+            }
+            line.push('}');
+            writeln!(buf, "{}", line)
+        });
+    }
+    let logger = CountingLogger { inner: builder.build() };
+    let level = match logger.inner.filter() {
+        level if args.warnings_as_errors => level.max(log::LevelFilter::Warn),
+        level => level,
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+/// Quote a string for JSON.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,80 @@
+use crate::*;
+use std::{
+    ops::Deref,
+    sync::Mutex,
+    time::Duration,
+};
+
+/// A pool of connections to one database file, for importing several files into it at once.
+/// The database is switched to WAL mode while the pool is open, and every connection waits up to the busy timeout
+/// for a lock rather than failing straight away. SQLite still only lets one connection write at a time, so inserts
+/// are serialised; what overlaps is reading and parsing the CSV files.
+pub struct ConnectionPool {
+    path: PathBuf,
+    busy_timeout: Duration,
+
+    /// Connections which have been opened and given back.
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl ConnectionPool {
+    /// Make a pool for the database which `conn` has open at `path`.
+    pub fn new(conn: &Connection, path: &Path, busy_timeout: Duration) -> Result<Self, ImportError> {
+        conn.busy_timeout(busy_timeout)?;
+        let mode: String = conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+        if !mode.eq_ignore_ascii_case("wal") {
+            warn!("couldn't switch '{}' to WAL mode (it's in {} mode), so imports may wait on each other more", path.display(), mode);
+        }
+        Ok(ConnectionPool {
+            path: path.to_path_buf(),
+            busy_timeout,
+            idle: Mutex::new(vec![]),
+        })
+    }
+
+    /// Take a connection from the pool, opening a new one if they're all in use.
+    pub fn get(&self) -> Result<PooledConnection<'_>, ImportError> {
+        let idle = self.idle.lock().unwrap().pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => {
+                let conn = open_database(&self.path, OpenMode::Existing)?;
+                conn.busy_timeout(self.busy_timeout)?;
+                conn
+            },
+        };
+        Ok(PooledConnection { pool: self, conn: Some(conn) })
+    }
+
+    /// Close every connection in the pool, and put the database back into its usual journal mode.
+    /// `conn` is the connection the pool was made with.
+    pub fn close(self, conn: &Connection) -> Result<(), ImportError> {
+        for idle in self.idle.into_inner().unwrap() {
+            idle.close().map_err(|(_, er)| er)?;
+        }
+        let _: String = conn.pragma_update_and_check(None, "journal_mode", "DELETE", |row| row.get(0))?;
+        Ok(())
+    }
+}
+
+/// A connection borrowed from a pool, which goes back into it when dropped.
+pub struct PooledConnection<'a> {
+    pool: &'a ConnectionPool,
+    conn: Option<Connection>,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.idle.lock().unwrap().push(conn);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::*;
+use std::time::{Duration, Instant};
+
+/// How often the progress line is redrawn.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Which part of an import an [`ImportProgress`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportStage {
+    /// Reading rows from the CSV file.
+    Reading,
+
+    /// Inserting rows into the table.
+    Inserting,
+}
+
+/// How far an import has got, passed to the callback given to [`import_csv`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportProgress {
+    /// The table being imported into.
+    pub table: String,
+
+    pub stage: ImportStage,
+
+    /// How many rows have been done so far in this stage.
+    pub rows: u64,
+
+    /// How many rows there are in total, if it's known (or estimated).
+    pub total_rows: Option<u64>,
+
+    /// How many bytes of the CSV file have been read.
+    pub bytes: u64,
+}
+
+/// A callback which is given progress while importing.
+pub type ProgressCallback<'a> = &'a mut dyn FnMut(ImportProgress);
+
+/// A progress line on stderr, shown with --progress.
+/// Progress is also logged with `info!` every --progress-interval rows, for when a live line isn't any use.
+/// Progress never goes to stdout, so it can't end up in output written there.
+/// A callback can be given as well, which is called as often as the line would be redrawn.
+pub struct Progress<'a> {
+    /// What's being counted, e.g. "Reading".
+    label: String,
+
+    /// How many rows there are in total, if it's known (or estimated).
+    total: Option<u64>,
+
+    /// How many rows have been done so far.
+    done: u64,
+
+    /// When the line was last drawn.
+    last_drawn: Option<Instant>,
+
+    /// Set with --progress.
+    enabled: bool,
+
+    /// Log a line every this many rows; 0 turns it off.
+    interval: u64,
+
+    /// How many bytes of the source have been read.
+    bytes: u64,
+
+    /// The table and stage to report to the callback with, and the callback itself.
+    callback: Option<(String, ImportStage, ProgressCallback<'a>)>,
+}
+
+impl<'a> Progress<'a> {
+    pub fn new(args: &Arguments, label: &str, total: Option<u64>) -> Self {
+        Progress {
+            label: label.to_string(),
+            total,
+            done: 0,
+            last_drawn: None,
+            enabled: args.progress,
+            interval: args.progress_interval,
+            bytes: 0,
+            callback: None,
+        }
+    }
+
+    /// Report progress to a callback too, if there is one.
+    pub fn with_callback(mut self, table: &str, stage: ImportStage, callback: Option<ProgressCallback<'a>>) -> Self {
+        self.callback = callback.map(|callback| (table.to_string(), stage, callback));
+        self
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Set how many bytes of the source have been read so far.
+    pub fn set_bytes(&mut self, bytes: u64) {
+        self.bytes = bytes;
+    }
+
+    /// Count some more rows as done, redrawing the line if it's been a while.
+    pub fn tick(&mut self, rows: u64) {
+        let before = self.done;
+        self.done += rows;
+        if self.interval > 0 && self.done / self.interval > before / self.interval {
+            info!("{}: {} rows", self.label, self.done);
+        }
+        if !self.enabled && self.callback.is_none() {
+            return;
+        }
+        if self.last_drawn.is_none_or(|drawn| drawn.elapsed() >= REDRAW_INTERVAL) {
+            self.draw();
+        }
+    }
+
+    /// Draw the final count and move on to a new line.
+    pub fn finish(&mut self) {
+        if self.interval > 0 {
+            info!("{}: {} rows in total", self.label, self.done);
+        }
+        // An estimate can be off, so don't leave the line short of (or past) 100%.
+        self.total = Some(self.done);
+        if self.enabled || self.callback.is_some() {
+            self.draw();
+        }
+        if self.enabled {
+            eprintln!();
+        }
+    }
+
+    fn draw(&mut self) {
+        self.last_drawn = Some(Instant::now());
+        if let Some((table, stage, callback)) = self.callback.as_mut() {
+            callback(ImportProgress {
+                table: table.clone(),
+                stage: *stage,
+                rows: self.done,
+                total_rows: self.total,
+                bytes: self.bytes,
+            });
+        }
+        if !self.enabled {
+            return;
+        }
+
+        match self.total {
+            Some(total) if total > 0 => {
+                let percent = (self.done as f64 / total as f64 * 100.0).min(100.0);
+                eprint!("\r{}: {}/{} rows ({:.0}%)", self.label, self.done, total, percent);
+            },
+            _ => eprint!("\r{}: {} rows", self.label, self.done),
+        }
+    }
+}
+
+/// Estimate how many rows are in a file by counting its newlines.
+/// This is much cheaper than parsing the file, but over-counts when quoted fields contain newlines.
+pub fn estimate_rows(path: &Path) -> std::io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0; 64 * 1024];
+    let mut lines = 0;
+    loop {
+        let count = std::io::Read::read(&mut file, &mut buf)?;
+        if count == 0 {
+            break;
+        }
+        lines += buf[..count].iter().filter(|&&b| b == b'\n').count() as u64;
+    }
+    Ok(lines)
+}
+
+/// Borrow a callback again for a shorter time, so it can be handed on more than once.
+pub fn reborrow_callback<'a>(callback: &'a mut Option<ProgressCallback>) -> Option<ProgressCallback<'a>> {
+    match callback {
+        Some(callback) => Some(&mut **callback),
+        None => None,
+    }
+}
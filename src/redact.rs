@@ -0,0 +1,92 @@
+use crate::*;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::str::FromStr;
+
+/// What --redact replaces a column's values with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactMode {
+    /// A fixed mask, so nothing of the value is kept.
+    Mask,
+    /// An HMAC-SHA-256 keyed with the salt, so equal values can still be matched up and counted.
+    Hash,
+}
+
+impl RedactMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RedactMode::Mask => "mask",
+            RedactMode::Hash => "hash",
+        }
+    }
+}
+
+/// The value every masked value is replaced with.
+pub const MASK: &str = "***";
+
+/// A column to redact, given as COLUMN:mask or COLUMN:hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnRedaction {
+    pub column: String,
+    pub mode: RedactMode,
+}
+
+impl FromStr for ColumnRedaction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split at the last colon, so column names can contain colons.
+        let (column, mode) = s.rsplit_once(':')
+            .ok_or_else(|| format!("expected COLUMN:mask or COLUMN:hash but got '{}'", s))?;
+        let mode = match mode.trim().to_lowercase().as_str() {
+            "mask" => RedactMode::Mask,
+            "hash" => RedactMode::Hash,
+            _ => return Err(format!("unknown redaction '{}'; expected mask or hash", mode)),
+        };
+        if column.is_empty() {
+            return Err(format!("no column name in '{}'", s));
+        }
+        Ok(ColumnRedaction { column: column.to_string(), mode })
+    }
+}
+
+/// Work out how each column in the header is redacted, if it is.
+/// A column can't be redacted as well as read as a percentage, since what's stored is the mask or hash.
+pub fn column_redactions(header: &[&str], pipelines: &[Vec<Operation>], args: &Arguments) -> Result<Vec<Option<RedactMode>>, ImportError> {
+    let mut redactions = vec![None; header.len()];
+    for redaction in &args.redactions {
+        let index = header.iter()
+            .position(|h| *h == redaction.column)
+            .ok_or_else(|| ImportError::Other(format!("Redact column '{}' doesn't exist!", redaction.column)))?;
+        if pipelines[index].last() == Some(&Operation::Percent) {
+            return Err(ImportError::Other(format!("Column '{}' is redacted, so it can't be a percentage!", redaction.column)));
+        }
+        redactions[index] = Some(redaction.mode);
+    }
+    if redactions.contains(&Some(RedactMode::Hash)) && args.redact_salt.is_none() {
+        warn!("hashing without --redact-salt, so common values can be found by hashing guesses");
+    }
+    Ok(redactions)
+}
+
+/// How a column is redacted, if it is, going by its name.
+pub fn redaction_for(column: &str, args: &Arguments) -> Option<RedactMode> {
+    args.redactions.iter().rev().find(|redaction| redaction.column == column).map(|redaction| redaction.mode)
+}
+
+/// Replace a value with its mask or hash.
+/// Empty values are left empty, since there is nothing in them to hide.
+pub fn redact(mode: RedactMode, value: &str, args: &Arguments) -> String {
+    if value.is_empty() {
+        return String::new();
+    }
+    match mode {
+        RedactMode::Mask => MASK.to_string(),
+        RedactMode::Hash => {
+            let salt = args.redact_salt.as_deref().unwrap_or("");
+            let mut hash = Hmac::<Sha256>::new_from_slice(salt.as_bytes()).expect("HMAC takes a key of any length");
+            hash.update(value.as_bytes());
+            to_hex(&hash.finalize().into_bytes())
+        },
+    }
+}
@@ -0,0 +1,150 @@
+use crate::*;
+use rusqlite::{Batch, Statement};
+use std::io::BufRead;
+
+/// What to do with uncommitted changes when the REPL exits, set with --repl-commit-on-exit.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitOnExit {
+    /// Ask whether to commit them. If there's no answer (stdin is closed), they're rolled back with a warning.
+    Ask,
+    /// Commit them.
+    Always,
+    /// Roll them back.
+    Never,
+}
+
+/// Query the database interactively once the import is done, for --repl.
+/// Statements end with a semicolon and can run over several lines, and several on one line are run in turn.
+/// Everything happens in a transaction, so nothing is saved until `.commit` (or COMMIT), or until the REPL exits and
+/// --repl-commit-on-exit says to keep the changes.
+/// The prompt goes to stderr and results go to stdout, one row per line with tab-separated values.
+pub fn run_repl(conn: &Connection, args: &Arguments) -> Result<(), ImportError> {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut out = std::io::stdout().lock();
+
+    eprintln!("Entering REPL mode. End statements with ';', and type .help for commands.");
+    conn.execute_batch("BEGIN;")?;
+    let mut uncommitted = false;
+    let mut statement = String::new();
+    loop {
+        eprint!("{}", if statement.is_empty() { "sqlite> " } else { "   ...> " });
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            // stdin was closed, which counts as quitting.
+            eprintln!();
+            break;
+        }
+
+        let trimmed = line.trim();
+        if statement.is_empty() && trimmed.starts_with('.') {
+            match trimmed {
+                ".quit" | ".exit" => break,
+                ".commit" => {
+                    conn.execute_batch("COMMIT; BEGIN;")?;
+                    uncommitted = false;
+                },
+                ".rollback" => {
+                    conn.execute_batch("ROLLBACK; BEGIN;")?;
+                    uncommitted = false;
+                },
+                ".tables" => {
+                    let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name;")?;
+                    let names = stmt.query_map([], |row| row.get::<_, String>(0))?;
+                    for name in names {
+                        writeln!(out, "{}", name?)?;
+                    }
+                },
+                ".help" => {
+                    eprintln!(".tables     List the tables and views.");
+                    eprintln!(".commit     Save the changes made so far.");
+                    eprintln!(".rollback   Throw away the changes made since the last .commit.");
+                    eprintln!(".quit       Leave the REPL (as does .exit, or closing stdin).");
+                },
+                _ => eprintln!("Unknown command '{}'; type .help for the commands.", trimmed),
+            }
+            continue;
+        }
+
+        statement.push_str(&line);
+        if !statement.trim_end().ends_with(';') {
+            continue;
+        }
+        if let Err(er) = run_statements(conn, &statement, &mut uncommitted, &mut out) {
+            eprintln!("Error: {}", er);
+        }
+        // Some errors roll the transaction back.
+        keep_transaction(conn, &mut uncommitted)?;
+        statement.clear();
+    }
+
+    let commit = match args.repl_commit_on_exit {
+        _ if !uncommitted => false,
+        CommitOnExit::Always => true,
+        CommitOnExit::Never => false,
+        CommitOnExit::Ask => {
+            eprint!("There are uncommitted changes. Commit them? [y/N] ");
+            let mut answer = String::new();
+            if input.read_line(&mut answer)? == 0 {
+                eprintln!();
+                warn!("no answer, so the uncommitted changes were rolled back; use --repl-commit-on-exit to choose beforehand");
+            }
+            matches!(answer.trim(), "y" | "Y" | "yes")
+        },
+    };
+    conn.execute_batch(if commit { "COMMIT;" } else { "ROLLBACK;" })?;
+    Ok(())
+}
+
+/// Run each statement in turn, writing out any rows they return, and stopping at the first which fails.
+/// `uncommitted` is set if one might have changed anything since the last commit.
+fn run_statements<W: Write>(conn: &Connection, sql: &str, uncommitted: &mut bool, out: &mut W) -> Result<(), ImportError> {
+    let mut batch = Batch::new(conn, sql);
+    while let Some(mut stmt) = batch.next()? {
+        *uncommitted |= run_statement(&mut stmt, out)?;
+        keep_transaction(conn, uncommitted)?;
+    }
+    Ok(())
+}
+
+/// Begin a new transaction if the last one was ended, by a COMMIT or ROLLBACK say.
+/// Either way, nothing is left uncommitted.
+fn keep_transaction(conn: &Connection, uncommitted: &mut bool) -> Result<(), ImportError> {
+    if conn.is_autocommit() {
+        conn.execute_batch("BEGIN;")?;
+        *uncommitted = false;
+    }
+    Ok(())
+}
+
+/// Run one statement, writing out any rows it returns.
+/// Returns whether it might have changed anything. Statements like SAVEPOINT, or a PRAGMA which only reads, don't
+/// count, but an INSERT with a RETURNING clause does.
+fn run_statement<W: Write>(stmt: &mut Statement, out: &mut W) -> Result<bool, ImportError> {
+    let changes = !stmt.readonly();
+    let columns = stmt.column_count();
+    if columns == 0 {
+        stmt.raw_execute()?;
+        return Ok(changes);
+    }
+
+    let mut rows = stmt.raw_query();
+    while let Some(row) = rows.next()? {
+        let values = (0..columns)
+            .map(|ii| row.get::<_, Value>(ii).map(|value| display_value(&value)))
+            .collect::<Result<Vec<String>>>()?;
+        writeln!(out, "{}", values.join("\t"))?;
+    }
+    Ok(changes)
+}
+
+/// Show a value the way the sqlite3 shell does.
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Integer(number) => number.to_string(),
+        Value::Real(number) => number.to_string(),
+        Value::Text(text) => text.clone(),
+        Value::Blob(_) => sql_literal(value),
+    }
+}
@@ -0,0 +1,83 @@
+use crate::*;
+
+/// The table --summary-table records each file's import in.
+pub const RUNS_TABLE: &str = "_import_runs";
+
+/// How importing a file went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Imported,
+    /// Skipped by --skip-if-unchanged.
+    Skipped,
+    Failed,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Imported => "imported",
+            RunStatus::Skipped => "skipped",
+            RunStatus::Failed => "failed",
+        }
+    }
+}
+
+/// What happened to one input file.
+#[derive(Debug, Clone)]
+pub struct FileRun {
+    pub source: PathBuf,
+    pub table_name: String,
+    pub status: RunStatus,
+    pub rows: u64,
+    /// How many row errors and warnings there were, of the kinds counted for --summary-only.
+    pub errors: u64,
+    /// Why the file failed, if it did.
+    pub message: Option<String>,
+}
+
+/// Import a file, logging any failure, and keep track of how it went.
+pub fn import_tracked(conn: &Connection, args: &Arguments, path: &Path, table_name: &str, dedup_report: Option<&mut csv::Writer<File>>) -> FileRun {
+    let errors_before = thread_row_errors();
+    let result = import_file(conn, args, path, table_name, dedup_report, None);
+    let (status, rows, message) = match result {
+        Ok(Some(rows)) => (RunStatus::Imported, rows, None),
+        Ok(None) => (RunStatus::Skipped, 0, None),
+        Err(er) => {
+            error!("Error importing '{}': {}", path.display(), er);
+            (RunStatus::Failed, 0, Some(er.to_string()))
+        },
+    };
+    FileRun {
+        source: path.to_path_buf(),
+        table_name: table_name.to_string(),
+        status,
+        rows,
+        errors: thread_row_errors().since(&errors_before).total(),
+        message,
+    }
+}
+
+/// Add a row to the --summary-table for each file imported by this run.
+/// The table keeps the rows from earlier runs, and they all share a `run_at` time so they can be told apart.
+pub fn write_import_runs(conn: &Connection, runs: &[FileRun], run_at: u64) -> Result<(), ImportError> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (\"id\" INTEGER PRIMARY KEY AUTOINCREMENT, run_at INTEGER, source TEXT, table_name TEXT, status TEXT, rows INTEGER, errors INTEGER, message TEXT);",
+        quote_identifier(RUNS_TABLE),
+    ))?;
+    let mut stmt = conn.prepare(&format!(
+        "INSERT INTO {} (run_at, source, table_name, status, rows, errors, message) VALUES (?, ?, ?, ?, ?, ?, ?);",
+        quote_identifier(RUNS_TABLE),
+    ))?;
+    for run in runs {
+        stmt.execute(rusqlite::params![
+            run_at as i64,
+            run.source.display().to_string(),
+            run.table_name,
+            run.status.as_str(),
+            run.rows as i64,
+            run.errors as i64,
+            run.message,
+        ])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use crate::*;
+
+/// A small, fast random number generator (SplitMix64), for --sample-rows.
+/// It doesn't need to be cryptographically strong; what matters is that the same seed always picks the same rows.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to (but not including) `bound`, without the bias of taking a remainder.
+    fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let number = self.next();
+            if number < zone {
+                return number % bound;
+            }
+        }
+    }
+}
+
+/// A uniformly random sample of a fixed number of items from a sequence of unknown length (Algorithm R).
+/// Only the sample is ever held, so it takes the same memory however long the sequence is.
+pub struct Reservoir<T> {
+    capacity: usize,
+    seen: u64,
+    items: Vec<T>,
+    rng: SplitMix64,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(capacity: usize, seed: u64) -> Self {
+        Reservoir { capacity, seen: 0, items: Vec::new(), rng: SplitMix64 { state: seed } }
+    }
+
+    /// Offer the next item, which is kept with the chance it'd have of being in a sample of everything seen so far.
+    pub fn offer(&mut self, item: T) {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+            return;
+        }
+        let index = self.rng.below(self.seen);
+        if index < self.capacity as u64 {
+            self.items[index as usize] = item;
+        }
+    }
+
+    /// How many items have been offered.
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// The sample, in no particular order.
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+/// The seed for --sample-rows: --seed if it's given, and otherwise one from the clock, which is logged so the same
+/// sample can be taken again.
+pub fn sample_seed(args: &Arguments) -> u64 {
+    if let Some(seed) = args.seed {
+        return seed;
+    }
+    if args.deterministic {
+        return 0;
+    }
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    info!("sampling rows with --seed {}", seed);
+    seed
+}
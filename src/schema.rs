@@ -0,0 +1,49 @@
+use crate::*;
+
+/// A column in a --column-order-from-header-file schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaColumn {
+    pub name: String,
+    /// The type the column is stored as, or None to leave it to the usual rules.
+    pub column_type: Option<ColumnType>,
+}
+
+/// Read the columns of a schema file, for --column-order-from-header-file.
+/// There's a column on each line, as NAME or NAME:TYPE, where the type is INTEGER, REAL or TEXT. Blank lines and
+/// lines starting with '#' are ignored, and names are trimmed.
+pub fn read_schema_file(path: &Path) -> Result<Vec<SchemaColumn>, ImportError> {
+    let contents = std::fs::read_to_string(path)?;
+    let bad_line = |number: usize, message: String| {
+        ImportError::Other(format!("line {} of schema file '{}': {}", number + 1, path.display(), message))
+    };
+
+    let mut columns: Vec<SchemaColumn> = vec![];
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // Split at the last colon, so column names can contain colons.
+        let (name, column_type) = match line.rsplit_once(':') {
+            Some((name, declared)) => {
+                let column_type = ColumnType::from_sql(declared.trim())
+                    .ok_or_else(|| bad_line(number, format!("unknown type '{}'; expected INTEGER, REAL or TEXT", declared.trim())))?;
+                (name.trim(), Some(column_type))
+            },
+            None => (line, None),
+        };
+        if name.is_empty() {
+            return Err(bad_line(number, "no column name".to_string()));
+        }
+        if columns.iter().any(|column| column.name == name) {
+            return Err(bad_line(number, format!("column '{}' is already in the schema", name)));
+        }
+        columns.push(SchemaColumn { name: name.to_string(), column_type });
+    }
+
+    if columns.is_empty() {
+        return Err(ImportError::Other(format!("schema file '{}' has no columns", path.display())));
+    }
+    Ok(columns)
+}
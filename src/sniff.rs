@@ -0,0 +1,84 @@
+use crate::*;
+
+/// How much of the start of an input is looked at to work out what it is.
+pub const SNIFF_BYTES: usize = 64 * 1024;
+
+/// Delimiters --input-format auto chooses between, in the order they're preferred when it's a tie.
+const CANDIDATES: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// How many lines --input-format auto looks at to choose the delimiter.
+const SNIFF_LINES: usize = 20;
+
+/// Kinds of input file.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Work it out from the contents: the delimiter is whichever of comma, tab, semicolon and pipe splits the most
+    /// lines into the same number of fields. Files which look like JSON or gzip are rejected, since they can't be read.
+    Auto,
+    /// Comma-separated.
+    Csv,
+    /// Tab-separated.
+    Tsv,
+}
+
+/// Work out the delimiter of an input from its first bytes.
+/// A --delimiter always wins. Otherwise it depends on the --input-format.
+pub fn input_delimiter(args: &Arguments, start: &[u8]) -> std::io::Result<u8> {
+    if let Some(delimiter) = args.delimiter {
+        return Ok(delimiter as u8);
+    }
+    match args.input_format {
+        InputFormat::Csv => return Ok(b','),
+        InputFormat::Tsv => return Ok(b'\t'),
+        InputFormat::Auto => (),
+    }
+
+    let unreadable = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string());
+    if start.starts_with(&[0x1f, 0x8b]) {
+        return Err(unreadable("this looks like a gzip file, which can't be imported; decompress it first"));
+    }
+    let text = start.strip_prefix("\u{feff}".as_bytes()).unwrap_or(start);
+    if matches!(text.iter().find(|b| !b.is_ascii_whitespace()), Some(b'[' | b'{')) {
+        return Err(unreadable("this looks like JSON, which can't be imported; use --input-format csv if it really is CSV"));
+    }
+
+    // Only the start of the input is here, so the last line may have been cut off; it's left out unless it's the
+    // only one.
+    let mut lines = text.split(|&b| b == b'\n').collect::<Vec<&[u8]>>();
+    if lines.len() > 1 && !text.ends_with(b"\n") {
+        lines.pop();
+    }
+    lines.retain(|line| !line.iter().all(|b| b.is_ascii_whitespace()) && !line.starts_with(b"#"));
+    lines.truncate(SNIFF_LINES);
+
+    let mut best = (b',', 0);
+    for candidate in CANDIDATES {
+        let counts = lines.iter().map(|line| count_unquoted(line, candidate)).collect::<Vec<usize>>();
+        // The score is how many lines have the most common number of fields.
+        let score = counts.iter()
+            .filter(|&&count| count > 0)
+            .map(|count| counts.iter().filter(|&c| c == count).count())
+            .max()
+            .unwrap_or(0);
+        if score > best.1 {
+            best = (candidate, score);
+        }
+    }
+    info!("guessed that the delimiter is {:?}", best.0 as char);
+    Ok(best.0)
+}
+
+/// Count how many times a delimiter appears in a line, outside of quotes.
+fn count_unquoted(line: &[u8], delimiter: u8) -> usize {
+    let mut quoted = false;
+    let mut count = 0;
+    for &b in line {
+        if b == b'"' {
+            quoted = !quoted;
+        }
+        else if b == delimiter && !quoted {
+            count += 1;
+        }
+    }
+    count
+}
@@ -5,14 +5,9 @@ use crate::*;
 ///     table_name          The name of the table you want to create.
 ///     table_columns       A vector of (column_name, column_type) tuples.
 ///     conn                A sqlite::Connection to work with.
-pub fn create_table(conn: &Connection, table_name: &str, table_columns: Vec<(&str, &str)>) -> Result<()> {
-    let columns = table_columns.iter()
-        .map(|(column_name, column_type)| format!(r#""{}" {}"#, column_name, column_type))
-        .collect::<Vec<String>>()
-        .join(", ");
-    let query = format!(r#"
-    CREATE TABLE IF NOT EXISTS "{}" ("id" INTEGER PRIMARY KEY AUTOINCREMENT, {});
-    "#, table_name, columns);
+///     key                 How the rows are identified; see `TableKey`.
+pub fn create_table(conn: &Connection, table_name: &str, table_columns: Vec<(&str, &str)>, key: TableKey) -> Result<()> {
+    let query = build_create_table_query(table_name, &table_columns, key);
 
     let mut stmt = conn.prepare_cached(query.as_ref())?;
     stmt.execute([])?;
@@ -20,33 +15,338 @@ pub fn create_table(conn: &Connection, table_name: &str, table_columns: Vec<(&st
     Ok(())
 }
 
-/// Get the next ID to use.
-pub fn get_last_rowid(conn: &Connection) -> usize {
-    let id: Result<i32> = 
-    conn.prepare_cached("SELECT last_insert_rowid();").unwrap()
-        .query_row(params![], |r| r.get(0));
-    let id = match id {
-        Ok(id) if id >= 0 => id + 1,
-        // Ignore errors and just start with id = 0.
-        Ok(_id) => 0,
-        Err(_) => 0, 
+/// Get the names of a table's columns, in order.
+/// This is empty if the table doesn't exist.
+pub fn table_column_names(conn: &Connection, table_name: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({});", quote_identifier(table_name)))?;
+    let names = stmt.query_map([], |r| r.get::<_, String>(1))?
+        .collect::<Result<Vec<String>>>()?;
+    Ok(names)
+}
+
+/// Get the names and declared types of a table's columns, in order.
+/// This is empty if the table doesn't exist.
+pub fn table_column_types(conn: &Connection, table_name: &str) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({});", quote_identifier(table_name)))?;
+    let columns = stmt.query_map([], |r| Ok((r.get::<_, String>(1)?, r.get::<_, String>(2)?)))?
+        .collect::<Result<Vec<(String, String)>>>()?;
+    Ok(columns)
+}
+
+/// Change the declared types of some of a table's columns, given as (column, old type, new type).
+/// SQLite can't alter a column's type, so the table is rebuilt from its CREATE TABLE statement with the types swapped,
+/// and its rows are copied across; its indexes and triggers are then made again. The columns have to be declared the
+/// way `create_table` declares them. Everything happens in a savepoint, so a failure leaves the table as it was.
+pub fn change_column_types(conn: &Connection, table_name: &str, changes: &[(&str, &str, &str)]) -> Result<(), ImportError> {
+    let create: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?;", [table_name], |row| row.get(0),
+    )?;
+    let mut stmt = conn.prepare("SELECT sql FROM sqlite_master WHERE type IN ('index', 'trigger') AND tbl_name = ? AND sql IS NOT NULL;")?;
+    let dependents = stmt.query_map([table_name], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<String>>>()?;
+
+    let temporary = unused_table_name(conn, &format!("{}_promoted", table_name))?;
+    let mut rebuilt = create.replacen(&quote_identifier(table_name), &quote_identifier(&temporary), 1);
+    for (column, old_type, new_type) in changes {
+        // Each column's declaration comes straight after the opening bracket or a comma.
+        let declaration = ["(", ", "].iter()
+            .map(|before| (format!("{}{} {}", before, quote_identifier(column), old_type), before))
+            .find(|(old, _)| rebuilt.contains(old.as_str()));
+        let Some((old, before)) = declaration else {
+            return Err(ImportError::Other(format!("can't find how column '{}' is declared in '{}'", column, table_name)));
         };
-    id.max(0) as usize
+        rebuilt = rebuilt.replacen(&old, &format!("{}{} {}", before, quote_identifier(column), new_type), 1);
+    }
+
+    let mut script = format!("SAVEPOINT change_column_types;\n{};\n", rebuilt);
+    // Generated columns can't be inserted into, and table_info leaves them out.
+    let columns = table_column_names(conn, table_name)?.iter()
+        .map(|name| quote_identifier(name))
+        .collect::<Vec<String>>()
+        .join(", ");
+    script.push_str(&format!("INSERT INTO {} ({}) SELECT {} FROM {};\n", quote_identifier(&temporary), columns, columns, quote_identifier(table_name)));
+    script.push_str(&format!("DROP TABLE {};\n", quote_identifier(table_name)));
+    script.push_str(&format!("ALTER TABLE {} RENAME TO {};\n", quote_identifier(&temporary), quote_identifier(table_name)));
+    for dependent in dependents {
+        script.push_str(&format!("{};\n", dependent));
+    }
+    script.push_str("RELEASE change_column_types;");
+    if let Err(er) = conn.execute_batch(&script) {
+        if !conn.is_autocommit() {
+            conn.execute_batch("ROLLBACK TO change_column_types; RELEASE change_column_types;")?;
+        }
+        return Err(er.into());
+    }
+    Ok(())
+}
+
+/// Add a column to an existing table.
+pub fn add_column(conn: &Connection, table_name: &str, column_name: &str, column_type: &str) -> Result<()> {
+    let query = format!("ALTER TABLE {} ADD COLUMN {} {};", quote_identifier(table_name), quote_identifier(column_name), column_type);
+    conn.execute(&query, [])?;
+    Ok(())
+}
+
+/// A view to create once everything has been imported, given with --create-view as `NAME AS SELECT ...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewDefinition {
+    pub name: String,
+    pub select: String,
+}
+
+impl std::str::FromStr for ViewDefinition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.to_ascii_uppercase()
+            .find(" AS ")
+            .ok_or_else(|| format!("expected 'NAME AS SELECT ...' but got '{}'", s))?;
+        let name = s[..split].trim().trim_matches('"');
+        let select = s[split + 4..].trim().trim_end_matches(';');
+        if name.is_empty() || select.is_empty() {
+            return Err(format!("expected 'NAME AS SELECT ...' but got '{}'", s));
+        }
+        Ok(ViewDefinition { name: name.to_string(), select: select.to_string() })
+    }
+}
+
+/// A collation for a column, given with --collate as COLUMN:COLLATION.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnCollation {
+    pub column: String,
+    pub collation: String,
+}
+
+impl std::str::FromStr for ColumnCollation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Collation names can't contain colons, so the column name is everything up to the last one.
+        let (column, collation) = s.rsplit_once(':')
+            .ok_or_else(|| format!("expected COLUMN:COLLATION but got '{}'", s))?;
+        if column.is_empty() || collation.trim().is_empty() {
+            return Err(format!("expected COLUMN:COLLATION but got '{}'", s));
+        }
+        Ok(ColumnCollation { column: column.to_string(), collation: collation.trim().to_string() })
+    }
 }
 
-/// Add a row to a table.
-pub fn add_row(conn: &Connection, table_name: &str, columns: &[&str], values: &[&str], where_clause: Option<&str>) -> Result<(), rusqlite::Error> {
+/// A column computed from the others, given with --generated-column as "NAME = EXPR".
+/// The expression can end with STORED to have the values stored rather than computed when they're read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedColumn {
+    pub name: String,
+    pub expression: String,
+    pub stored: bool,
+}
+
+impl GeneratedColumn {
+    /// How the column is declared in a CREATE TABLE statement, after its name.
+    pub fn definition(&self) -> String {
+        format!("GENERATED ALWAYS AS ({}) {}", self.expression, if self.stored { "STORED" } else { "VIRTUAL" })
+    }
+}
+
+impl std::str::FromStr for GeneratedColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, expression) = s.split_once('=')
+            .ok_or_else(|| format!("expected 'NAME = EXPR' but got '{}'", s))?;
+        let name = name.trim().trim_matches('"');
+        let mut expression = expression.trim();
+        let mut stored = false;
+        for (keyword, is_stored) in [("STORED", true), ("VIRTUAL", false)] {
+            let split = expression.len().saturating_sub(keyword.len());
+            if expression.is_char_boundary(split) && expression[split..].eq_ignore_ascii_case(keyword)
+                && expression[..split].ends_with(char::is_whitespace) {
+                expression = expression[..split].trim_end();
+                stored = is_stored;
+            }
+        }
+        if name.is_empty() || expression.is_empty() {
+            return Err(format!("expected 'NAME = EXPR' but got '{}'", s));
+        }
+        Ok(GeneratedColumn { name: name.to_string(), expression: expression.to_string(), stored })
+    }
+}
+
+/// Check that a --generated-column makes sense alongside a table's other columns, before the table is created.
+/// SQLite only checks the expression when the table is made, so a scratch table is made and thrown away.
+pub fn check_generated_column(conn: &Connection, table_columns: &[(&str, &str)], generated: &GeneratedColumn) -> Result<()> {
+    let definition = generated.definition();
+    let mut columns = table_columns.to_vec();
+    columns.push((&generated.name, &definition));
+    let query = build_create_table_query("_check_generated_column", &columns, TableKey::default())
+        .replacen("CREATE TABLE IF NOT EXISTS", "CREATE TEMP TABLE", 1);
+    conn.execute_batch("SAVEPOINT check_generated_column;")?;
+    let result = conn.execute_batch(&query);
+    conn.execute_batch("ROLLBACK TO check_generated_column; RELEASE check_generated_column;")?;
+    result
+}
+
+/// Work out the collation of each column from --collate, checking each is one the connection knows about.
+/// That's SQLite's BINARY, NOCASE and RTRIM, plus any an application has registered.
+pub fn column_collations(conn: &Connection, header: &[&str], args: &Arguments) -> Result<Vec<Option<String>>, ImportError> {
+    let mut stmt = conn.prepare("PRAGMA collation_list;")?;
+    let known = stmt.query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<String>>>()?;
+
+    let mut collations = vec![None; header.len()];
+    for collate in &args.collations {
+        let index = header.iter()
+            .position(|h| *h == collate.column)
+            .ok_or_else(|| ImportError::Other(format!("Collate column '{}' doesn't exist!", collate.column)))?;
+        let collation = known.iter()
+            .find(|name| name.eq_ignore_ascii_case(&collate.collation))
+            .ok_or_else(|| ImportError::Other(format!(
+                "There's no collation called '{}' (try one of {})", collate.collation, known.join(", "),
+            )))?;
+        collations[index] = Some(collation.clone());
+    }
+    Ok(collations)
+}
+
+/// Create (or replace) a view.
+/// SQLite doesn't check a view's tables and columns until it's used, so the SELECT is prepared first to catch mistakes.
+pub fn create_view(conn: &Connection, view: &ViewDefinition) -> Result<()> {
+    conn.prepare(&view.select)?;
+    conn.execute(&format!("DROP VIEW IF EXISTS {};", quote_identifier(&view.name)), [])?;
+    conn.execute(&format!("CREATE VIEW {} AS {};", quote_identifier(&view.name), view.select), [])?;
+    Ok(())
+}
+
+/// How a column is declared in a CREATE TABLE statement, after its name.
+pub fn column_definition(column_type: ColumnType, collation: Option<&str>) -> String {
+    match collation {
+        Some(collation) => format!("{} COLLATE {}", column_type.as_sql(), collation),
+        None => column_type.as_sql().to_string(),
+    }
+}
+
+/// How the rows of a table are identified, from --primary-key and --without-rowid.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TableKey<'a> {
+    /// The columns making up the primary key, in order. Without any, an "id" column is added to be the key.
+    pub primary_key: &'a [String],
+    /// Store the table WITHOUT ROWID. This needs a primary key.
+    pub without_rowid: bool,
+}
+
+impl<'a> TableKey<'a> {
+    pub fn from_args(args: &'a Arguments) -> Self {
+        TableKey { primary_key: &args.primary_key, without_rowid: args.without_rowid }
+    }
+
+    /// Check that every column of the primary key is in the header.
+    pub fn check(&self, header: &[&str]) -> Result<(), ImportError> {
+        match self.primary_key.iter().find(|name| !header.contains(&name.as_str())) {
+            Some(name) => Err(ImportError::Other(format!("Primary key column '{}' doesn't exist!", name))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Build the CREATE TABLE statement used by `create_table`.
+pub fn build_create_table_query(table_name: &str, table_columns: &[(&str, &str)], key: TableKey) -> String {
+    let mut columns = table_columns.iter()
+        .map(|(column_name, column_type)| format!("{} {}", quote_identifier(column_name), column_type))
+        .collect::<Vec<String>>();
+    if key.primary_key.is_empty() {
+        columns.insert(0, r#""id" INTEGER PRIMARY KEY AUTOINCREMENT"#.to_string());
+    } else {
+        let names = key.primary_key.iter().map(|name| quote_identifier(name)).collect::<Vec<String>>();
+        columns.push(format!("PRIMARY KEY ({})", names.join(", ")));
+    }
+    format!(r#"
+    CREATE TABLE IF NOT EXISTS {} ({}){};
+    "#, quote_identifier(table_name), columns.join(", "), if key.without_rowid { " WITHOUT ROWID" } else { "" })
+}
+
+/// A name for a new table, starting with `name`, which nothing in the database is called yet.
+fn unused_table_name(conn: &Connection, name: &str) -> Result<String> {
+    let mut candidate = name.to_string();
+    for ii in 2.. {
+        // SQLite's names aren't case sensitive.
+        let taken: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = ? COLLATE NOCASE);", [&candidate], |row| row.get(0),
+        )?;
+        if !taken {
+            break;
+        }
+        candidate = format!("{}{}", name, ii);
+    }
+    Ok(candidate)
+}
+
+/// Set the database's page size. This takes effect straight away for a new database, or at the next VACUUM.
+pub fn set_page_size(conn: &Connection, page_size: u32) -> Result<()> {
+    conn.pragma_update(None, "page_size", page_size)
+}
+
+/// Rebuild the database, packing it into as few pages as possible.
+pub fn vacuum(conn: &Connection) -> Result<()> {
+    conn.execute_batch("VACUUM;")
+}
+
+/// The largest rowid in a table, or 0 if it's empty.
+pub fn max_rowid(conn: &Connection, table_name: &str) -> Result<i64> {
+    conn.query_row(&format!("SELECT coalesce(max(rowid), 0) FROM {};", quote_identifier(table_name)), [], |row| row.get(0))
+}
+
+/// Check that a --row-filter-sql expression makes sense for a table, before anything is inserted.
+pub fn check_row_filter(conn: &Connection, table_name: &str, filter: &str) -> Result<()> {
+    conn.prepare(&format!("SELECT 1 FROM {} WHERE ({});", quote_identifier(table_name), filter))?;
+    Ok(())
+}
+
+/// Delete the rows after `after_rowid` which don't match a --row-filter-sql expression, returning how many went.
+/// Rows the expression gives NULL for don't match, just as they wouldn't in a WHERE clause.
+pub fn delete_unmatched_rows(conn: &Connection, table_name: &str, filter: &str, after_rowid: i64) -> Result<usize> {
+    let query = format!("DELETE FROM {} WHERE rowid > ? AND NOT coalesce(({}), 0);", quote_identifier(table_name), filter);
+    conn.execute(&query, [after_rowid])
+}
+
+/// The table which --verify-checksum records each source file's checksum in.
+pub const METADATA_TABLE: &str = "_import_metadata";
+
+/// Record the checksum of a source file imported into a table, replacing any from an earlier import.
+pub fn record_checksum(conn: &Connection, source: &str, table_name: &str, checksum: &str, imported_at: u64) -> Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (source TEXT, table_name TEXT, checksum TEXT, imported_at INTEGER, PRIMARY KEY (source, table_name));",
+        quote_identifier(METADATA_TABLE),
+    ))?;
+    conn.execute(
+        &format!("INSERT OR REPLACE INTO {} VALUES (?, ?, ?, ?);", quote_identifier(METADATA_TABLE)),
+        rusqlite::params![source, table_name, checksum, imported_at as i64],
+    )?;
+    Ok(())
+}
+
+/// The checksum recorded the last time a source file was imported into a table, if it was.
+pub fn stored_checksum(conn: &Connection, source: &str, table_name: &str) -> Result<Option<String>> {
+    if table_column_names(conn, METADATA_TABLE)?.is_empty() {
+        return Ok(None);
+    }
+    let query = format!("SELECT checksum FROM {} WHERE source = ? AND table_name = ?;", quote_identifier(METADATA_TABLE));
+    match conn.query_row(&query, [source, table_name], |row| row.get(0)) {
+        Ok(checksum) => Ok(Some(checksum)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(er) => Err(er),
+    }
+}
+
+/// Add a row to a table, returning its rowid.
+/// Missing values are filled in with empty strings. With an upsert's ON CONFLICT clause as the where_clause, a row
+/// whose key is already there may be left alone, and then the rowid is of the last row which was inserted.
+pub fn add_row(conn: &Connection, table_name: &str, columns: &[&str], values: &[Value], where_clause: Option<&str>) -> Result<i64, rusqlite::Error> {
     // We need to keep track of how many columns/values we need to 
-    let longest = 0
-        .max(columns.len())
-        .max(values.len());
+    let longest = columns.len().max(values.len());
 
-    let values: Vec<String> = pad_row(&values, "", longest);
-    let columns: Vec<String> = pad_row(&columns, "", longest);
+    let values: Vec<Value> = pad_row(values, Value::Text(String::new()), longest);
+    let columns: Vec<&str> = pad_row(columns, "", longest);
 
-    let placeholder = build_placeholder(longest);
-    let column_names = columns.iter().map(|c| format!(r#""{}""#, c)).collect::<Vec<String>>().join(", ");
-    let query = format!(r#"INSERT INTO "{}" ({}) VALUES ({}) {};"#, table_name, &column_names, placeholder, where_clause.unwrap_or(""));
+    let query = build_insert_query(table_name, &columns, where_clause);
     let mut stmt = conn.prepare(&query)?;
 
     // Bind the parameters.
@@ -54,29 +354,166 @@ pub fn add_row(conn: &Connection, table_name: &str, columns: &[&str], values: &[
         stmt.raw_bind_parameter(jj + 1, val)?;
     }
 
-    match stmt.raw_execute() {
-        Ok(1) => Ok(()),
-        Ok(n) => { 
-            warn!("unexpected number of rows affected: {}", n); 
-            Ok(())
-        },
-        Err(er) => {
-            error!("error adding a row! {}", er);
-            Err(er)
-        }
+    let changed = stmt.raw_execute()?;
+    // An upsert which does nothing with a row that's already there leaves it alone.
+    let expected = if where_clause.is_some() { 0..=1 } else { 1..=1 };
+    if !expected.contains(&changed) {
+        warn!("unexpected number of rows affected: {}", changed);
+    }
+    Ok(conn.last_insert_rowid())
+}
+
+/// Get the columns of a table's primary key, in order.
+/// This is empty if the table doesn't exist, or has no primary key.
+pub fn table_primary_key(conn: &Connection, table_name: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({});", quote_identifier(table_name)))?;
+    let mut key = stmt.query_map([], |r| Ok((r.get::<_, i64>(5)?, r.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<(i64, String)>>>()?;
+    key.retain(|(position, _)| *position > 0);
+    key.sort();
+    Ok(key.into_iter().map(|(_, name)| name).collect())
+}
+
+/// Build the clause which turns the INSERT made by `add_row` into an upsert on a table's primary key, for
+/// --merge-into. A row whose key is already in the table has its other columns updated; if every column is part of
+/// the key, there's nothing to update, so the row is left alone.
+pub fn build_upsert_clause(key: &[String], columns: &[&str]) -> String {
+    let key_names = key.iter().map(|name| quote_identifier(name)).collect::<Vec<String>>().join(", ");
+    let updates = columns.iter()
+        .filter(|column| !key.iter().any(|name| name == *column))
+        .map(|column| format!("{0} = excluded.{0}", quote_identifier(column)))
+        .collect::<Vec<String>>();
+    if updates.is_empty() {
+        format!("ON CONFLICT ({}) DO NOTHING", key_names)
+    } else {
+        format!("ON CONFLICT ({}) DO UPDATE SET {}", key_names, updates.join(", "))
     }
 }
 
+/// Build the INSERT statement used by `add_row`, with a placeholder for each column.
+pub fn build_insert_query(table_name: &str, columns: &[&str], where_clause: Option<&str>) -> String {
+    let placeholder = build_placeholder(columns.len());
+    let column_names = columns.iter().map(|c| quote_identifier(c)).collect::<Vec<String>>().join(", ");
+    format!("INSERT INTO {} ({}) VALUES ({}) {};", quote_identifier(table_name), &column_names, placeholder, where_clause.unwrap_or(""))
+}
+
 fn build_placeholder(len: usize) -> String {
     let question_marks = (0..len).map(|_| "?").collect::<Vec<&str>>();
     question_marks.join(", ")
 }
 
-fn pad_row(values: &[&str], pad: &str, pad_to: usize) -> Vec<String> {
-    let mut result: Vec<String> = values.iter().map(|x| x.to_string()).collect();
+fn pad_row<T: Clone>(values: &[T], pad: T, pad_to: usize) -> Vec<T> {
+    let mut result: Vec<T> = values.to_vec();
     for _ii in values.len()..pad_to {
-        result.push(pad.to_string());
+        result.push(pad.clone());
     }
 
     result
-}
\ No newline at end of file
+}
+
+/// Write every table and view in the database out as a SQL script.
+/// Each one is dropped and recreated, so the script can be run more than once. Views come after all of the tables
+/// and their data, in the order they were created, so whatever they select from is already there.
+pub fn dump_sql<W: std::io::Write>(conn: &Connection, out: &mut W) -> Result<(), ImportError> {
+    let tables = schema_entries(conn, "table")?;
+    let views = schema_entries(conn, "view")?;
+
+    writeln!(out, "BEGIN TRANSACTION;")?;
+    for (name, create) in tables {
+        writeln!(out, "DROP TABLE IF EXISTS {};", quote_identifier(&name))?;
+        writeln!(out, "{};", create)?;
+
+        // Generated columns are left out, since they can't be inserted into.
+        let columns = table_column_names(conn, &name)?.iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM {};", columns, quote_identifier(&name)))?;
+        let column_count = stmt.column_count();
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let values = (0..column_count)
+                .map(|ii| row.get::<_, Value>(ii).map(|value| sql_literal(&value)))
+                .collect::<Result<Vec<String>>>()?;
+            writeln!(out, "INSERT INTO {} ({}) VALUES ({});", quote_identifier(&name), columns, values.join(", "))?;
+        }
+    }
+    for (name, create) in views {
+        writeln!(out, "DROP VIEW IF EXISTS {};", quote_identifier(&name))?;
+        writeln!(out, "{};", create)?;
+    }
+    writeln!(out, "COMMIT;")?;
+
+    Ok(())
+}
+
+/// Find the name and CREATE statement of everything of a type ("table" or "view") in the database, in the order
+/// they were created. SQLite's own tables are left out.
+fn schema_entries(conn: &Connection, entry_type: &str) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, sql FROM sqlite_master WHERE type = ?1 AND name NOT LIKE 'sqlite_%' ORDER BY rowid;"
+    )?;
+    let entries = stmt
+        .query_map([entry_type], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<(String, String)>>>();
+    entries
+}
+
+/// Quote a table or column name, escaping any quotes inside it.
+pub fn quote_identifier(name: &str) -> String {
+    format!(r#""{}""#, name.replace('"', r#""""#))
+}
+
+/// Write a value as a SQL literal.
+pub fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(number) => number.to_string(),
+        // SQL has no literals for infinity or NaN. A number too big for a REAL reads back as infinity, and SQLite
+        // stores NaN as NULL anyway.
+        Value::Real(number) if number.is_nan() => "NULL".to_string(),
+        Value::Real(number) if number.is_infinite() => if *number > 0.0 { "9e999" } else { "-9e999" }.to_string(),
+        // Debug formatting always includes a decimal point or exponent, so this reads back as REAL.
+        Value::Real(number) => format!("{:?}", number),
+        Value::Text(text) => format!("'{}'", text.replace('\'', "''")),
+        Value::Blob(bytes) => {
+            let hex = bytes.iter().map(|b| format!("{:02X}", b)).collect::<String>();
+            format!("X'{}'", hex)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dumped_reals_read_back() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE numbers (x REAL);").unwrap();
+        for number in [1.5, -0.25, 1e300, f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            conn.execute("INSERT INTO numbers VALUES (?);", [number]).unwrap();
+        }
+        let mut script = vec![];
+        dump_sql(&conn, &mut script).unwrap();
+
+        let copy = Connection::open_in_memory().unwrap();
+        copy.execute_batch(std::str::from_utf8(&script).unwrap()).unwrap();
+        let mut stmt = copy.prepare("SELECT x FROM numbers ORDER BY rowid;").unwrap();
+        let numbers = stmt.query_map([], |r| r.get::<_, Option<f64>>(0))
+            .unwrap()
+            .collect::<Result<Vec<Option<f64>>>>()
+            .unwrap();
+        assert_eq!(numbers, [Some(1.5), Some(-0.25), Some(1e300), Some(f64::INFINITY), Some(f64::NEG_INFINITY), None]);
+    }
+
+    #[test]
+    fn add_row_returns_the_rowid() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE people (name TEXT, age INTEGER);").unwrap();
+        let values = [Value::Text("ann".to_string()), Value::Integer(30)];
+        assert_eq!(add_row(&conn, "people", &["name", "age"], &values, None).unwrap(), 1);
+        conn.execute_batch("INSERT INTO people (rowid, name) VALUES (10, 'bob');").unwrap();
+        assert_eq!(add_row(&conn, "people", &["name"], &values[..1], None).unwrap(), 11);
+    }
+}
@@ -0,0 +1,478 @@
+use crate::*;
+use std::{
+    borrow::Cow,
+    io::{BufRead, BufReader},
+};
+
+/// The most parameters SQLite allows in one statement, so big batches are split into several INSERTs.
+const MAX_PARAMETERS: usize = 32766;
+
+/// The savepoint the rows since the last commit are inserted in.
+const SAVEPOINT: &str = "streaming_import";
+
+/// A row waiting to be inserted, with its row number and line for error messages.
+struct BufferedRow {
+    row: u64,
+    line: u64,
+    values: Vec<Value>,
+}
+
+/// Inserts rows into a table as they're read, for --streaming, rather than holding the whole file in memory.
+/// Rows are buffered until there are --batch-size of them, then inserted with a multi-row INSERT. They go in inside a
+/// savepoint which is released every --commit-every rows (or more often with --connection-pool), which commits them
+/// unless there's a transaction already open; if the import fails, only the rows since the last commit are rolled
+/// back.
+pub struct StreamingImporter<'a> {
+    conn: &'a Connection,
+    args: &'a Arguments,
+    table_name: &'a str,
+    header: Vec<String>,
+    column_types: Vec<ColumnType>,
+    percent_columns: Vec<usize>,
+    redactions: Vec<Option<RedactMode>>,
+    null_tokens: Vec<Vec<&'a str>>,
+    upsert: Option<String>,
+    first_rowid: i64,
+    buffer: Vec<BufferedRow>,
+    in_savepoint: bool,
+    uncommitted: u64,
+    inserted: u64,
+}
+
+impl<'a> StreamingImporter<'a> {
+    /// Create the table, with a column of the given type for each header name, or get an existing one ready for
+    /// the rows, just as without --streaming. Values in the `percent_columns` are read as percentages, and the
+    /// `redactions` say which columns --redact replaces the values of.
+    pub fn new(conn: &'a Connection, args: &'a Arguments, table_name: &'a str, header: Vec<String>, column_types: Vec<ColumnType>, percent_columns: Vec<usize>, redactions: Vec<Option<RedactMode>>) -> Result<Self, ImportError> {
+        let names = header.iter().map(|name| name.as_str()).collect::<Vec<&str>>();
+        let null_tokens = column_null_tokens(&names, args)?;
+        let collations = column_collations(conn, &names, args)?;
+        let definitions = column_types.iter()
+            .zip(&collations)
+            .map(|(column_type, collation)| column_definition(*column_type, collation.as_deref()))
+            .collect::<Vec<String>>();
+        let generated_definitions = args.generated_columns.iter()
+            .map(|generated| generated.definition())
+            .collect::<Vec<String>>();
+        let table_columns = table_columns(conn, args, &names, &definitions, &generated_definitions)?;
+        let ImportTarget { upsert, first_rowid } = prepare_table(conn, args, table_name, &names, &column_types, &table_columns)?;
+
+        Ok(StreamingImporter {
+            conn, args, table_name, header, column_types, percent_columns, redactions, null_tokens, upsert, first_rowid,
+            buffer: Vec::with_capacity(args.batch_size),
+            in_savepoint: false,
+            uncommitted: 0,
+            inserted: 0,
+        })
+    }
+
+    /// Convert a row to the values to store and buffer it, inserting the buffer once it's full.
+    /// `row` counts from 1, and `values` have already been through their columns' transforms. A value of None is
+    /// missing from the file, and stored as NULL. Values which can't be stored as their column's type are handled by
+    /// --on-type-error; with abort, the rows since the last commit are rolled back.
+    pub fn push(&mut self, row: u64, line: u64, values: &[Option<Cow<str>>]) -> Result<(), ImportError> {
+        let args = self.args;
+        let mut record = Vec::with_capacity(self.header.len());
+        for (ii, column_type) in self.column_types.iter().enumerate() {
+            let Some(value) = values.get(ii).map_or(Some(""), |value| value.as_deref()) else {
+                record.push(Value::Null);
+                continue;
+            };
+            let is_percent = self.percent_columns.contains(&ii) && !value.is_empty();
+            let converted = if self.null_tokens[ii].contains(&value) {
+                Some(Value::Null)
+            } else if let Some(mode) = self.redactions[ii] {
+                Some(Value::Text(redact(mode, value, args)))
+            } else if is_percent {
+                parse_percent(value, args).map(Value::Real)
+            } else {
+                to_sql_value(value, *column_type, args)
+            };
+            if let Some(converted) = converted {
+                record.push(converted);
+                continue;
+            }
+
+            let message = format!(
+                "row #{}: can't store '{}' in column '{}' as {}",
+                row, value, self.header[ii], if is_percent { "a percentage" } else { column_type.as_sql() },
+            );
+            match args.on_type_error {
+                TypeErrorAction::Abort => return Err(ImportError::Other(message)),
+                TypeErrorAction::Null => record.push(Value::Null),
+                TypeErrorAction::Text => record.push(Value::Text(value.to_string())),
+                TypeErrorAction::Skip => (),
+            }
+            let at = RowLocation { row: Some(row), line: Some(line), column: Some(&self.header[ii]) };
+            row_warning(args, ErrorCategory::TypeMismatch, at, format_args!("{}", message));
+            if args.on_type_error == TypeErrorAction::Skip {
+                return Ok(());
+            }
+        }
+
+        self.buffer.push(BufferedRow { row, line, values: record });
+        if self.buffer.len() >= args.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Insert every buffered row, then commit if --commit-every rows have gone in since the last commit.
+    fn flush(&mut self) -> Result<(), ImportError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        if !self.in_savepoint {
+            self.conn.execute_batch(&format!("SAVEPOINT {};", SAVEPOINT))?;
+            self.in_savepoint = true;
+        }
+
+        let rows = std::mem::take(&mut self.buffer);
+        let per_statement = (MAX_PARAMETERS / self.header.len().max(1)).max(1);
+        for batch in rows.chunks(per_statement) {
+            // A failed INSERT changes nothing, so when one row in the batch is bad, the rows are inserted one at a
+            // time to find it, just as they would be without --streaming.
+            if let Err(er) = self.insert_batch(batch) {
+                debug!("inserting a batch of {} rows failed ({}), so inserting them one at a time", batch.len(), er);
+                self.insert_one_at_a_time(batch);
+            }
+        }
+        self.uncommitted += rows.len() as u64;
+        self.buffer = rows;
+        self.buffer.clear();
+
+        // With --connection-pool, the other connections can't write until this one commits.
+        let commit_every = if self.args.connection_pool > 1 { self.args.commit_every.min(POOL_COMMIT_EVERY) } else { self.args.commit_every };
+        if self.uncommitted >= commit_every {
+            self.commit()?;
+        }
+        Ok(())
+    }
+
+    fn insert_batch(&mut self, batch: &[BufferedRow]) -> Result<(), rusqlite::Error> {
+        let names = self.header.iter().map(|name| name.as_str()).collect::<Vec<&str>>();
+        let query = build_batch_insert_query(self.table_name, &names, batch.len(), self.upsert.as_deref());
+        let mut stmt = self.conn.prepare_cached(&query)?;
+        for (jj, value) in batch.iter().flat_map(|row| &row.values).enumerate() {
+            stmt.raw_bind_parameter(jj + 1, value)?;
+        }
+        stmt.raw_execute()?;
+        self.inserted += batch.len() as u64;
+        Ok(())
+    }
+
+    fn insert_one_at_a_time(&mut self, batch: &[BufferedRow]) {
+        let names = self.header.iter().map(|name| name.as_str()).collect::<Vec<&str>>();
+        for row in batch {
+            match add_row(self.conn, self.table_name, &names, &row.values, self.upsert.as_deref()) {
+                Ok(_) => self.inserted += 1,
+                Err(er) => {
+                    let at = RowLocation { row: Some(row.row), line: Some(row.line), column: None };
+                    row_error(self.args, ErrorCategory::of_insert_error(&er), at, format_args!("error adding row #{}: {}", row.row, er));
+                },
+            }
+        }
+    }
+
+    fn commit(&mut self) -> Result<(), ImportError> {
+        if self.in_savepoint {
+            self.conn.execute_batch(&format!("RELEASE {};", SAVEPOINT))?;
+            self.in_savepoint = false;
+        }
+        self.uncommitted = 0;
+        Ok(())
+    }
+
+    /// Insert whatever is left in the buffer and commit, returning how many rows were inserted.
+    /// Any which don't match --row-filter-sql are removed then.
+    pub fn finish(mut self) -> Result<u64, ImportError> {
+        self.flush()?;
+        self.commit()?;
+        Ok(self.inserted - filter_rows(self.conn, self.args, self.table_name, self.first_rowid)?)
+    }
+}
+
+impl Drop for StreamingImporter<'_> {
+    /// Roll back anything not yet committed, since the import didn't finish.
+    fn drop(&mut self) {
+        if self.in_savepoint {
+            warn!("rolling back the {} rows of '{}' inserted since the last commit", self.uncommitted, self.table_name);
+            if let Err(er) = self.conn.execute_batch(&format!("ROLLBACK TO {0}; RELEASE {0};", SAVEPOINT)) {
+                error!("Error rolling back: {}", er);
+            }
+        }
+    }
+}
+
+/// Build an INSERT statement for several rows at once, with a placeholder for each of their values.
+/// An `upsert` clause for --merge-into goes on the end.
+fn build_batch_insert_query(table_name: &str, columns: &[&str], rows: usize, upsert: Option<&str>) -> String {
+    let column_names = columns.iter().map(|c| quote_identifier(c)).collect::<Vec<String>>().join(", ");
+    let row = format!("({})", vec!["?"; columns.len()].join(", "));
+    format!("INSERT INTO {} ({}) VALUES {} {};", quote_identifier(table_name), column_names, vec![row; rows].join(", "), upsert.unwrap_or(""))
+}
+
+/// Open a file for reading a record at a time, with the delimiter worked out as --input-format says.
+fn open_records(args: &Arguments, path: &Path) -> Result<RecordSource<'static>, ImportError> {
+    let mut source = BufReader::with_capacity(SNIFF_BYTES, File::open(path)?);
+    let delimiter = input_delimiter(args, source.fill_buf()?)?;
+    Ok(RecordSource::open(args, source, delimiter)?)
+}
+
+/// Read the header of a file, as it is in the file, if it has one.
+fn read_header(args: &Arguments, source: &mut RecordSource) -> Result<Option<Vec<String>>, ImportError> {
+    let header = match source.comment_header.take() {
+        Some(header) => Some(header),
+        None if args.use_header => Some(source.reader.headers()?.iter().map(|name| name.to_string()).collect()),
+        None => None,
+    };
+    if let Some(header) = header.as_ref() {
+        check_column_count(args, header.len(), "The header")?;
+    }
+    Ok(header)
+}
+
+/// Read the rows of a file for one of the passes, calling `on_row` with each one's row number (counting from 1),
+/// line and record. As without --streaming, a blank last line isn't a row, nor are repeats of the header with
+/// --skip-repeated-headers, and only the --rows range is read. With `report` set, the records which can't be parsed
+/// (and the repeated headers) are reported, so each pass doesn't report them again.
+fn read_rows(args: &Arguments, source: RecordSource, raw_header: Option<&[String]>, report: bool, context: Option<&ErrorContext>, progress: &mut Progress, mut on_row: impl FnMut(u64, u64, csv::StringRecord) -> Result<(), ImportError>) -> Result<(), ImportError> {
+    let RecordSource { reader, lines, .. } = source;
+    let mut data_rows = 0;
+    let mut row = 0;
+    let mut repeated_headers = 0;
+    let mut records = reader.into_records();
+    let mut lookahead = None;
+    while let Some(record) = lookahead.take().or_else(|| records.next()) {
+        progress.set_bytes(records.reader().position().byte());
+        let record = match record {
+            Ok(record) if is_trailing_blank(&record, &mut records, &mut lookahead) => continue,
+            Ok(record) => record,
+            Err(er) if er.is_io_error() || args.rfc4180 => return Err(er.into()),
+            Err(er) => {
+                if report {
+                    let at = RowLocation { line: er.position().map(|pos| pos.line() + lines), ..Default::default() };
+                    row_error(args, ErrorCategory::Parse, at, format_args!("Error reading CSV file: {}", er));
+                    if let (Some(context), Some(pos), false) = (context, er.position(), args.summary_only) {
+                        context.show(pos.line() + lines);
+                    }
+                }
+                continue;
+            },
+        };
+
+        let line = record.position().map_or(0, |pos| pos.line()) + lines;
+        let is_header = raw_header.is_some_and(|header| record.iter().eq(header.iter().map(|h| h.as_str())));
+        if args.skip_repeated_headers && is_header {
+            if report {
+                warn!("dropping a repeat of the header on line {}", line);
+            }
+            repeated_headers += 1;
+            continue;
+        }
+
+        data_rows += 1;
+        if let Some(range) = args.rows.as_ref() {
+            if data_rows > range.end {
+                break;
+            }
+            if data_rows < range.start {
+                continue;
+            }
+        }
+        row += 1;
+        progress.tick(1);
+        on_row(row, line, record)?;
+    }
+
+    if report && repeated_headers > 0 {
+        warn!("dropped {} repeats of the header", repeated_headers);
+    }
+    Ok(())
+}
+
+/// How the records of a file become the rows of its table.
+/// It's worked out from the header, and used on both passes, so they agree about every row.
+struct RowShape<'a> {
+    args: &'a Arguments,
+
+    /// The columns of the table. Longer rows add columns, unless there's a schema.
+    header: Vec<String>,
+
+    /// For --column-order-from-header-file, the column of the file each column of the table comes from.
+    schema_indices: Option<Vec<Option<usize>>>,
+
+    pipelines: Vec<Vec<Operation>>,
+}
+
+impl RowShape<'_> {
+    /// Put a record's values in the table's columns and run them through their transforms, adding columns for any
+    /// values past the end of the header. A value of None is missing from the file.
+    fn shape<'r>(&mut self, record: &'r csv::StringRecord) -> Vec<Option<Cow<'r, str>>> {
+        let values = match self.schema_indices.as_ref() {
+            Some(indices) => indices.iter()
+                .map(|index| index.map(|index| record.get(index).unwrap_or("")))
+                .collect::<Vec<Option<&str>>>(),
+            None => {
+                // Longer rows get columns with default names, just as when the whole file is read in.
+                for ii in self.header.len()..record.len() {
+                    self.header.push(format!("{}{}", self.args.default_column_name, ii + 1));
+                }
+                (0..self.header.len()).map(|ii| Some(record.get(ii).unwrap_or(""))).collect()
+            },
+        };
+        values.iter()
+            .enumerate()
+            .map(|(ii, value)| value.map(|value| match self.pipelines.get(ii).filter(|operations| !operations.is_empty()) {
+                Some(operations) => Cow::Owned(apply_operations(operations, value)),
+                None => Cow::Borrowed(value),
+            }))
+            .collect()
+    }
+}
+
+/// Import a file with a `StreamingImporter`, for --streaming, returning how many rows were inserted.
+/// The file is read twice: once to find the columns, check the values against --validate and infer the types
+/// (keeping nothing but the type of each column so far), and once to insert the rows. Rows go through the same
+/// transforms, validation, redaction and conversion as without --streaming, and rows which can't be parsed are
+/// reported and left out, as usual.
+pub fn stream_csv(conn: &Connection, args: &Arguments, path: &Path, table_name: &str, mut on_progress: Option<ProgressCallback>) -> Result<u64, ImportError> {
+    let mut source = open_records(args, path)?;
+    let raw_header = read_header(args, &mut source)?;
+    let mut header = match raw_header.as_ref() {
+        Some(header) => header.iter().map(|name| rewrite_header(name, args)).collect::<Vec<String>>(),
+        None => first_row_names(args, path)?,
+    };
+
+    // With a schema, the table has its columns, and the rest are dropped.
+    let schema = args.column_order_from_header_file.as_deref().map(read_schema_file).transpose()?;
+    let schema_indices = match schema.as_ref() {
+        Some(schema) => {
+            let names = header.iter().map(|name| name.as_str()).collect::<Vec<&str>>();
+            let indices = schema_indices(&names, schema, args.reject_extra_columns)?;
+            header = schema.iter().map(|column| column.name.clone()).collect();
+            Some(indices)
+        },
+        None => None,
+    };
+
+    for (option, names) in [("Text", &args.text_columns), ("Infer", &args.infer_columns), ("Keep-text", &args.keep_text_columns)] {
+        if let Some(name) = names.iter().find(|name| !header.contains(name)) {
+            return Err(ImportError::Other(format!("{} column '{}' doesn't exist!", option, name)));
+        }
+    }
+    let names = header.iter().map(|name| name.as_str()).collect::<Vec<&str>>();
+    let pipelines = column_pipelines(&names, args)?;
+    for (name, operations) in header.iter().zip(&pipelines) {
+        if args.keep_text_columns.contains(name) && !operations.is_empty() {
+            return Err(ImportError::Other(format!("Column '{}' is kept as text, so it can't be transformed!", name)));
+        }
+    }
+    let validations = column_validations(&names, args)?;
+    let mut null_tokens = column_null_tokens(&names, args)?;
+    let mut redactions = column_redactions(&names, &pipelines, args)?;
+    let percent_columns = (0..header.len())
+        .filter(|&ii| pipelines[ii].last() == Some(&Operation::Percent))
+        .collect::<Vec<usize>>();
+
+    // Columns get their types in the same order of precedence as without --streaming. The ones which aren't fixed
+    // are inferred from their values.
+    let fixed_types = (0..header.len())
+        .map(|ii| if percent_columns.contains(&ii) {
+            Some(ColumnType::Real)
+        } else if redactions[ii].is_some()
+            || args.keep_text_columns.contains(&header[ii])
+            || args.text_columns.contains(&header[ii]) {
+            Some(ColumnType::Text)
+        } else if let Some(column_type) = schema.as_ref().and_then(|schema| schema[ii].column_type) {
+            Some(column_type)
+        } else if !args.infer_types || (!args.infer_columns.is_empty() && !args.infer_columns.contains(&header[ii])) {
+            Some(ColumnType::Text)
+        } else {
+            None
+        })
+        .collect::<Vec<Option<ColumnType>>>();
+
+    // The first pass. Each inferred column's type is None until it has a value.
+    let context = ErrorContext::new(args, path);
+    let mut progress = Progress::new(args, "Reading", None)
+        .with_callback(table_name, ImportStage::Reading, reborrow_callback(&mut on_progress));
+    let mut shape = RowShape { args, header, schema_indices, pipelines };
+    let mut column_types: Vec<Option<ColumnType>> = vec![None; shape.header.len()];
+    let mut rows = 0;
+    let mut failures = 0;
+    read_rows(args, source, raw_header.as_deref(), true, context.as_ref(), &mut progress, |row, line, record| {
+        rows = row;
+        let columns = shape.header.len();
+        let values = shape.shape(&record);
+        if shape.header.len() > columns {
+            column_types.resize(shape.header.len(), None);
+            let names = shape.header.iter().map(|name| name.as_str()).collect::<Vec<&str>>();
+            null_tokens = column_null_tokens(&names, args)?;
+        }
+
+        // Values are checked after their transforms, just as they would be without --streaming.
+        if !validations.is_empty() {
+            let checked = values.iter().map(|value| value.as_deref().unwrap_or("").to_string()).collect::<Vec<String>>();
+            failures += validate_row(&validations, row, Some(line), &checked, args)?;
+        }
+
+        for (ii, value) in values.iter().enumerate() {
+            let Some(value) = value.as_deref() else {
+                continue;
+            };
+            // Columns past the end of the header are only inferred if every column is.
+            let inferred = fixed_types.get(ii).map_or(args.infer_types && args.infer_columns.is_empty(), |fixed| fixed.is_none());
+            if !inferred || value.is_empty() || null_tokens[ii].contains(&value) || column_types[ii] == Some(ColumnType::Text) {
+                continue;
+            }
+            column_types[ii] = column_types[ii].max(Some(value_type(value, args)));
+        }
+        Ok(())
+    })?;
+    progress.finish();
+    if failures > 0 {
+        warn!("{} values in '{}' failed validation", failures, table_name);
+    }
+
+    let header = &shape.header;
+    let column_types = (0..header.len())
+        .map(|ii| fixed_types.get(ii).copied().flatten().or(column_types[ii]).unwrap_or(ColumnType::Text))
+        .collect::<Vec<ColumnType>>();
+    redactions.resize(header.len(), None);
+    for (name, column_type) in header.iter().zip(&column_types) {
+        debug!("column '{}' of '{}' is {}", name, table_name, column_type.as_sql());
+    }
+
+    if args.dry_run {
+        report(args, format_args!("Table '{}': {} rows, {} columns", table_name, rows, header.len()));
+        return Ok(0);
+    }
+
+    // The second pass.
+    let mut importer = StreamingImporter::new(conn, args, table_name, header.clone(), column_types, percent_columns, redactions)?;
+    let mut progress = Progress::new(args, &format!("Importing {}", table_name), Some(rows))
+        .with_callback(table_name, ImportStage::Inserting, on_progress);
+    let mut source = open_records(args, path)?;
+    read_header(args, &mut source)?;
+    read_rows(args, source, raw_header.as_deref(), false, None, &mut progress, |row, line, record| {
+        let values = shape.shape(&record);
+        importer.push(row, line, &values)
+    })?;
+    let inserted = importer.finish()?;
+    progress.finish();
+    Ok(inserted)
+}
+
+/// Name the columns of a file without a header after the first row's, as --default-column-name says.
+/// The first row is checked against --min-columns and --max-columns, as the header would be.
+fn first_row_names(args: &Arguments, path: &Path) -> Result<Vec<String>, ImportError> {
+    let RecordSource { reader, lines, .. } = open_records(args, path)?;
+    let first = reader.into_records().find_map(|record| record.ok());
+    let count = first.as_ref().map_or(0, |record| record.len());
+    if let Some(record) = first.as_ref() {
+        let line = record.position().map_or(0, |pos| pos.line()) + lines;
+        check_column_count(args, count, &format!("The first row (line {})", line))?;
+    }
+    Ok((0..count).map(|ii| format!("{}{}", args.default_column_name, ii + 1)).collect())
+}
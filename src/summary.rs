@@ -0,0 +1,146 @@
+use crate::*;
+use std::cell::Cell;
+
+/// The kinds of problem a single row can have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// The row couldn't be read from the CSV file.
+    Parse,
+
+    /// A value can't be read as its column's type. --on-type-error says whether it was stored as text or NULL, or its
+    /// row was skipped.
+    TypeMismatch,
+
+    /// A value didn't match its --validate pattern.
+    Validation,
+
+    /// The row broke a constraint on its table, like a UNIQUE or NOT NULL column.
+    Constraint,
+
+    /// The row couldn't be inserted for some other reason.
+    Insert,
+}
+
+impl ErrorCategory {
+    const ALL: [ErrorCategory; 5] = [
+        ErrorCategory::Parse,
+        ErrorCategory::TypeMismatch,
+        ErrorCategory::Validation,
+        ErrorCategory::Constraint,
+        ErrorCategory::Insert,
+    ];
+
+    fn describe(&self) -> &'static str {
+        match self {
+            ErrorCategory::Parse => "parse errors",
+            ErrorCategory::TypeMismatch => "type mismatches",
+            ErrorCategory::Validation => "validation failures",
+            ErrorCategory::Constraint => "constraint violations",
+            ErrorCategory::Insert => "other insert errors",
+        }
+    }
+
+    /// Work out whether a failed insert broke a constraint.
+    pub fn of_insert_error(er: &rusqlite::Error) -> Self {
+        match er.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::ConstraintViolation) => ErrorCategory::Constraint,
+            Some(rusqlite::ErrorCode::TypeMismatch) => ErrorCategory::TypeMismatch,
+            _ => ErrorCategory::Insert,
+        }
+    }
+}
+
+/// How many row errors of each kind there have been, for --summary-only and the --summary-table.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCounts {
+    counts: [u64; 5],
+}
+
+impl ErrorCounts {
+    const NONE: ErrorCounts = ErrorCounts { counts: [0; 5] };
+
+    pub fn get(&self, category: ErrorCategory) -> u64 {
+        self.counts[category as usize]
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The errors counted since `earlier`, which was taken on the same thread.
+    pub fn since(&self, earlier: &ErrorCounts) -> ErrorCounts {
+        ErrorCounts { counts: std::array::from_fn(|ii| self.counts[ii] - earlier.counts[ii]) }
+    }
+}
+
+thread_local! {
+    // How many row errors and warnings there have been on this thread, so each file's can be told apart when files
+    // are imported on several threads with --connection-pool, and each run's when there's more than one.
+    static THREAD_ROW_ERRORS: Cell<ErrorCounts> = const { Cell::new(ErrorCounts::NONE) };
+}
+
+/// How many row errors and warnings of each kind there have been on this thread.
+pub fn thread_row_errors() -> ErrorCounts {
+    THREAD_ROW_ERRORS.with(|counts| counts.get())
+}
+
+/// Count row errors which happened on another thread as if they were on this one.
+pub fn credit_thread_row_errors(other: ErrorCounts) {
+    THREAD_ROW_ERRORS.with(|counts| {
+        let mut total = counts.get();
+        for (count, more) in total.counts.iter_mut().zip(other.counts) {
+            *count += more;
+        }
+        counts.set(total);
+    });
+}
+
+fn count_row_error(category: ErrorCategory) {
+    THREAD_ROW_ERRORS.with(|counts| {
+        let mut total = counts.get();
+        total.counts[category as usize] += 1;
+        counts.set(total);
+    });
+}
+
+/// Log an error with a single row, and count it.
+/// With --summary-only, only the count is kept.
+pub fn row_error(args: &Arguments, category: ErrorCategory, at: RowLocation, message: std::fmt::Arguments) {
+    count_row_error(category);
+    if !args.summary_only {
+        let _location = log_location(at);
+        error!("{}", message);
+    }
+}
+
+/// Log a warning about a single row, and count it.
+/// With --summary-only, only the count is kept.
+pub fn row_warning(args: &Arguments, category: ErrorCategory, at: RowLocation, message: std::fmt::Arguments) {
+    count_row_error(category);
+    if args.summary_only {
+        // It still counts for --warnings-as-errors.
+        count_warning();
+    } else {
+        let _location = log_location(at);
+        warn!("{}", message);
+    }
+}
+
+/// Log how many row errors of each kind there were, for --summary-only.
+pub fn report_error_summary(args: &Arguments, counts: &ErrorCounts) {
+    if counts.total() == 0 {
+        info!("no rows had any errors");
+        return;
+    }
+
+    error!("{} row errors in total:", counts.total());
+    for category in ErrorCategory::ALL {
+        match counts.get(category) {
+            0 => (),
+            count if category == ErrorCategory::TypeMismatch => {
+                error!("    {} {} ({})", count, category.describe(), args.on_type_error.effect());
+            },
+            count => error!("    {} {}", count, category.describe()),
+        }
+    }
+}
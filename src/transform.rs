@@ -0,0 +1,256 @@
+use crate::*;
+use std::str::FromStr;
+
+/// One step in a column's transform pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// Remove leading and trailing whitespace.
+    Trim,
+
+    /// Lowercase the value.
+    Lower,
+
+    /// Uppercase the value.
+    Upper,
+
+    /// Replace every occurrence of the first string with the second.
+    Replace(String, String),
+
+    /// Remove HTML tags and decode entities.
+    StripHtml,
+
+    /// Read the value as a percentage and store it as a REAL fraction.
+    /// This has to be the last step, since what comes out isn't text any more.
+    Percent,
+}
+
+impl Operation {
+    /// Run a value through this step.
+    /// Percent is handled when the value is bound, so it leaves the text alone.
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            Operation::Trim => value.trim().to_string(),
+            Operation::Lower => value.to_lowercase(),
+            Operation::Upper => value.to_uppercase(),
+            Operation::Replace(from, to) => value.replace(from.as_str(), to),
+            Operation::StripHtml => strip_html(value),
+            Operation::Percent => value.to_string(),
+        }
+    }
+}
+
+/// A pipeline of operations for one column, given with --transform.
+/// The syntax is `COLUMN: op | op | ...`, where the operations are:
+///     trim                Remove leading and trailing whitespace.
+///     lower               Lowercase the value.
+///     upper               Uppercase the value.
+///     replace(a, b)       Replace every "a" with "b". Arguments can be single-quoted to include commas, spaces or
+///                         parentheses, with '' for a literal quote.
+///     strip_html          Remove HTML tags and decode entities like &amp;.
+///     percent             Store "12.5%" as the REAL 0.125. Must come last.
+/// Operations run left to right.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transform {
+    pub column: String,
+    pub operations: Vec<Operation>,
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The column name is everything up to the first colon, so replace() arguments can contain colons.
+        let (column, pipeline) = s.split_once(':')
+            .ok_or_else(|| format!("expected 'COLUMN: operations' but got '{}'", s))?;
+        let column = column.trim();
+        if column.is_empty() {
+            return Err(format!("no column name in '{}'", s));
+        }
+
+        let operations = split_outside_quotes(pipeline, '|')?
+            .iter()
+            .map(|op| parse_operation(op.trim()))
+            .collect::<Result<Vec<Operation>, String>>()?;
+        check_percent_is_last(&operations)?;
+
+        Ok(Transform { column: column.to_string(), operations })
+    }
+}
+
+/// Parse a single operation, like `trim` or `replace(a, b)`.
+fn parse_operation(op: &str) -> Result<Operation, String> {
+    let (name, arguments) = match op.split_once('(') {
+        Some((name, rest)) => {
+            let rest = rest.strip_suffix(')')
+                .ok_or_else(|| format!("missing ')' in '{}'", op))?;
+            let arguments = split_outside_quotes(rest, ',')?
+                .iter()
+                .map(|argument| unquote(argument.trim()))
+                .collect::<Vec<String>>();
+            (name.trim(), arguments)
+        },
+        None => (op, vec![]),
+    };
+
+    match (name.to_lowercase().as_str(), arguments.as_slice()) {
+        ("trim", []) => Ok(Operation::Trim),
+        ("lower", []) => Ok(Operation::Lower),
+        ("upper", []) => Ok(Operation::Upper),
+        ("percent", []) => Ok(Operation::Percent),
+        ("strip_html", []) => Ok(Operation::StripHtml),
+        ("replace", [from, to]) => Ok(Operation::Replace(from.clone(), to.clone())),
+        ("replace", _) => Err(format!("replace takes two arguments, in '{}'", op)),
+        ("", _) => Err("empty operation".to_string()),
+        (_, _) => Err(format!("unknown operation '{}'", op)),
+    }
+}
+
+/// Split on a separator, except where it's inside single quotes.
+fn split_outside_quotes(s: &str, separator: char) -> Result<Vec<String>, String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in s.chars() {
+        match c {
+            '\'' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            },
+            _ if c == separator && !in_quotes => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("unclosed quote in '{}'", s));
+    }
+    parts.push(current);
+    Ok(parts)
+}
+
+/// Remove single quotes from around an argument, turning '' back into '.
+fn unquote(argument: &str) -> String {
+    match argument.strip_prefix('\'').and_then(|a| a.strip_suffix('\'')) {
+        Some(inner) => inner.replace("''", "'"),
+        None => argument.to_string(),
+    }
+}
+
+fn check_percent_is_last(operations: &[Operation]) -> Result<(), String> {
+    match operations.iter().position(|op| *op == Operation::Percent) {
+        Some(index) if index + 1 != operations.len() => Err("percent must be the last operation".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Remove the tags from a value and decode its entities, for --strip-html.
+/// This isn't a real HTML parser: a tag is anything from a '<' followed by a letter, '/', '!' or '?' to the next
+/// '>', so other angle brackets are left alone. Tags which break up text, like <br> and <p>, become spaces.
+pub fn strip_html(value: &str) -> String {
+    let mut text = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let tag = &rest[start + 1..];
+        let is_tag = tag.starts_with(|c: char| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'));
+        match tag.find('>') {
+            Some(end) if is_tag => {
+                let name = tag[..end].trim_start_matches('/')
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .next()
+                    .unwrap_or("")
+                    .to_ascii_lowercase();
+                if matches!(name.as_str(), "br" | "p" | "div" | "li" | "tr" | "td" | "th" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6") {
+                    text.push(' ');
+                }
+                rest = &tag[end + 1..];
+            },
+            _ => {
+                text.push('<');
+                rest = tag;
+            },
+        }
+    }
+    text.push_str(rest);
+
+    let text = decode_entities(&text);
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Decode the common named HTML entities, and numeric ones like &#39; and &#x27;.
+/// Anything else that looks like an entity is left as it is.
+fn decode_entities(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| {
+                let name = &rest[1..end];
+                let c = match name {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    _ => match name.strip_prefix('#') {
+                        Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok().and_then(char::from_u32),
+                        Some(number) => number.parse::<u32>().ok().and_then(char::from_u32),
+                        None => None,
+                    },
+                };
+                c.map(|c| (c, end))
+            });
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Work out the pipeline for every column in the header.
+/// --strip-html comes first, then --lowercase-column, then each --transform in the order given, then --percent-column.
+pub fn column_pipelines(header: &[&str], args: &Arguments) -> Result<Vec<Vec<Operation>>, ImportError> {
+    let mut pipelines = vec![vec![]; header.len()];
+    let mut add = |name: &str, operations: &[Operation], what: &str| {
+        let index = header.iter()
+            .position(|h| *h == name)
+            .ok_or_else(|| ImportError::Other(format!("{} column '{}' doesn't exist!", what, name)))?;
+        pipelines[index].extend_from_slice(operations);
+        Ok::<(), ImportError>(())
+    };
+
+    for name in &args.strip_html_columns {
+        add(name, &[Operation::StripHtml], "HTML")?;
+    }
+    for name in &args.lowercase_columns {
+        add(name, &[Operation::Lower], "Lowercase")?;
+    }
+    for transform in &args.transforms {
+        add(&transform.column, &transform.operations, "Transform")?;
+    }
+    for name in &args.percent_columns {
+        add(name, &[Operation::Percent], "Percentage")?;
+    }
+
+    for (name, operations) in header.iter().zip(&pipelines) {
+        check_percent_is_last(operations)
+            .map_err(|er| ImportError::Other(format!("column '{}': {}", name, er)))?;
+    }
+    Ok(pipelines)
+}
+
+/// Run a value through a pipeline.
+pub fn apply_operations(operations: &[Operation], value: &str) -> String {
+    operations.iter().fold(value.to_string(), |value, op| op.apply(&value))
+}
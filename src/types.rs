@@ -0,0 +1,336 @@
+use crate::*;
+use std::{borrow::Cow, str::FromStr};
+
+/// The SQLite type given to a column.
+/// These are ordered from narrowest to widest, so a column can be widened with `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColumnType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl ColumnType {
+    /// The name of this type in a CREATE TABLE statement.
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            ColumnType::Integer => "INTEGER",
+            ColumnType::Real => "REAL",
+            ColumnType::Text => "TEXT",
+        }
+    }
+
+    /// Read a declared column type back, for the types this tool creates.
+    pub fn from_sql(declared: &str) -> Option<ColumnType> {
+        match declared.to_ascii_uppercase().as_str() {
+            "INTEGER" => Some(ColumnType::Integer),
+            "REAL" => Some(ColumnType::Real),
+            "TEXT" => Some(ColumnType::Text),
+            _ => None,
+        }
+    }
+}
+
+/// A value which means NULL in one column, given with --null-token-column as COLUMN:TOKEN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullToken {
+    pub column: String,
+    pub token: String,
+}
+
+impl FromStr for NullToken {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The column name is everything up to the first colon, so a token can contain colons.
+        let (column, token) = s.split_once(':')
+            .ok_or_else(|| format!("expected COLUMN:TOKEN but got '{}'", s))?;
+        if column.is_empty() {
+            return Err(format!("no column name in '{}'", s));
+        }
+        Ok(NullToken { column: column.to_string(), token: token.to_string() })
+    }
+}
+
+/// Work out which values mean NULL in each column, from --null-token and --null-token-column.
+/// --keep-text columns don't get the --null-token, and naming one with --null-token-column is an error.
+pub fn column_null_tokens<'a>(header: &[&str], args: &'a Arguments) -> Result<Vec<Vec<&'a str>>, ImportError> {
+    let mut tokens = header.iter()
+        .map(|name| match args.null_token.as_deref() {
+            Some(token) if !args.keep_text_columns.iter().any(|c| c == name) => vec![token],
+            _ => vec![],
+        })
+        .collect::<Vec<Vec<&str>>>();
+    for null_token in &args.null_token_columns {
+        let index = header.iter()
+            .position(|h| *h == null_token.column)
+            .ok_or_else(|| ImportError::Other(format!("Null token column '{}' doesn't exist!", null_token.column)))?;
+        if args.keep_text_columns.contains(&null_token.column) {
+            return Err(ImportError::Other(format!("Column '{}' is kept as text, so it can't have a null token!", null_token.column)));
+        }
+        tokens[index].push(&null_token.token);
+    }
+    Ok(tokens)
+}
+
+/// Work out the narrowest type which can hold every value in a column.
+/// Empty cells don't count towards the type, since they're stored as NULL in numeric columns.
+/// A column with no values at all is TEXT, and so is one holding codes like zip codes (see `looks_like_code`).
+pub fn infer_type(values: &[Option<&str>], args: &Arguments) -> ColumnType {
+    infer_type_explained(values, args).0
+}
+
+/// Infer a column's type, along with the first value which needed that type, for --explain.
+/// There's no value if the column is empty.
+pub fn infer_type_explained<'a>(values: &[Option<&'a str>], args: &Arguments) -> (ColumnType, Option<&'a str>) {
+    let mut column_type: Option<(ColumnType, &str)> = None;
+    for value in values.iter().flatten() {
+        if value.is_empty() {
+            continue;
+        }
+
+        let value_type = value_type(value, args);
+        if value_type == ColumnType::Text {
+            // Nothing is wider than TEXT, so stop looking.
+            return (ColumnType::Text, Some(value));
+        }
+        if column_type.is_none_or(|(t, _)| value_type > t) {
+            column_type = Some((value_type, value));
+        }
+    }
+    match column_type {
+        Some((column_type, value)) => (column_type, Some(value)),
+        None => (ColumnType::Text, None),
+    }
+}
+
+/// What a column's values look like, for --explain-types.
+#[derive(Debug, Default)]
+pub struct TypeEvidence {
+    pub integers: u64,
+    pub reals: u64,
+    pub texts: u64,
+    pub nulls: u64,
+    /// The first value which needed each wider type, with its row number (from 1), in the order they came.
+    pub widened: Vec<(ColumnType, u64, String)>,
+}
+
+impl TypeEvidence {
+    /// Count a value. Values are counted as the narrowest type which can hold them, so "3" is an integer even though
+    /// it's also a real, and codes like "02134" are text.
+    /// Empty values and null tokens should be given as None.
+    pub fn add(&mut self, row: u64, value: Option<&str>, args: &Arguments) {
+        let Some(value) = value.filter(|value| !value.is_empty()) else {
+            self.nulls += 1;
+            return;
+        };
+        let value_type = value_type(value, args);
+        match value_type {
+            ColumnType::Integer => self.integers += 1,
+            ColumnType::Real => self.reals += 1,
+            ColumnType::Text => self.texts += 1,
+        }
+        let widest = self.widened.last().map_or(ColumnType::Integer, |(column_type, _, _)| *column_type);
+        if value_type > widest {
+            self.widened.push((value_type, row, value.to_string()));
+        }
+    }
+}
+
+/// The narrowest type which can hold a single non-empty value.
+pub fn value_type(value: &str, args: &Arguments) -> ColumnType {
+    if looks_like_code(value, args) {
+        ColumnType::Text
+    }
+    else if parse_integer(value, args).is_some() {
+        ColumnType::Integer
+    }
+    else if parse_real(value, args).is_some() {
+        ColumnType::Real
+    }
+    else {
+        ColumnType::Text
+    }
+}
+
+/// Whether a value is made of digits but is really a code, like the zip code "02134" or a long account number.
+/// These have a leading zero or more than --max-integer-digits digits, and would be mangled by storing them as
+/// integers, so their columns are kept as TEXT.
+pub fn looks_like_code(value: &str, args: &Arguments) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    (digits.len() > 1 && digits.starts_with('0')) || digits.len() > args.max_integer_digits
+}
+
+/// Parse an integer, including scientific notation if --coerce-scientific is set.
+pub fn parse_integer(value: &str, args: &Arguments) -> Option<i64> {
+    let value = strip_currency(value, args);
+    match value.parse::<i64>() {
+        Ok(number) => Some(number),
+        Err(_) if args.coerce_scientific => parse_scientific_integer(&value),
+        Err(_) => None,
+    }
+}
+
+/// Parse a real number.
+/// Infinities and NaN are rejected, since SQLite can't store them sensibly.
+pub fn parse_real(value: &str, args: &Arguments) -> Option<f64> {
+    strip_currency(value, args).parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+/// Parse a percentage like "12.5%" into the fraction 0.125.
+/// The trailing '%' is optional, since every value in a percentage column is taken to be a percentage.
+pub fn parse_percent(value: &str, args: &Arguments) -> Option<f64> {
+    let value = value.trim();
+    let number = value.strip_suffix('%').unwrap_or(value);
+    parse_real(number.trim_end(), args).map(|number| number / 100.0)
+}
+
+/// Remove currency symbols and grouping separators from a value, if --strip-currency is set.
+/// This only happens while parsing numbers, so text columns keep their original values.
+/// Commas are only removed where they separate thousands, so "1,234,567.89" loses them but "1,2" keeps
+/// its comma, and isn't a number.
+pub fn strip_currency<'a>(value: &'a str, args: &Arguments) -> Cow<'a, str> {
+    if !args.strip_currency {
+        return Cow::Borrowed(value);
+    }
+
+    let stripped = value.chars()
+        .filter(|&c| !args.currency_symbols.contains(c))
+        .collect::<String>();
+    let stripped = stripped.trim();
+    Cow::Owned(strip_thousands_separators(stripped).unwrap_or_else(|| stripped.to_string()))
+}
+
+/// Remove the commas from a number like "-1,234,567.89", where each one is followed by three digits and the
+/// first follows one to three. Returns None if the value has commas anywhere else.
+fn strip_thousands_separators(value: &str) -> Option<String> {
+    let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if fraction.contains(',') {
+        return None;
+    }
+
+    let mut groups = whole.split(',');
+    let first = groups.next()?;
+    if !(1..=3).contains(&first.len()) || !first.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if !groups.all(|group| group.len() == 3 && group.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    Some(value.replace(',', ""))
+}
+
+/// Parse a value like "1.23E+15" which represents a whole number.
+/// The mantissa is read as a decimal rather than going through f64, so this is exact for anything in i64 range.
+/// Returns None if the value isn't in scientific notation, isn't whole, or doesn't fit in an i64.
+pub fn parse_scientific_integer(value: &str) -> Option<i64> {
+    let (mantissa, exponent) = value.split_once(['e', 'E'])?;
+    let exponent = exponent.parse::<i32>().ok()?;
+
+    let (negative, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    // Shift the decimal point by the exponent, dropping any trailing zeros it uncovers.
+    let digits = format!("{}{}", whole, fraction);
+    if digits.chars().all(|c| c == '0') {
+        // Zero is zero whatever the exponent.
+        return Some(0);
+    }
+    let shift = exponent.checked_sub(i32::try_from(fraction.len()).ok()?)?;
+    if shift > 19 {
+        // Any non-zero digit shifted this far is out of range for an i64.
+        return None;
+    }
+    let digits = if shift < 0 {
+        let keep = digits.len().checked_sub(shift.unsigned_abs() as usize)?;
+        let (digits, dropped) = digits.split_at(keep);
+        if dropped.chars().any(|c| c != '0') {
+            // There's a fractional part, so this isn't an integer.
+            return None;
+        }
+        digits.to_string()
+    }
+    else {
+        digits
+    };
+
+    let mut number: i128 = 0;
+    for c in digits.chars() {
+        number = number.checked_mul(10)?.checked_add(c.to_digit(10)? as i128)?;
+    }
+    for _ in 0..shift.max(0) {
+        number = number.checked_mul(10)?;
+        if number > i64::MAX as i128 + 1 {
+            return None;
+        }
+    }
+
+    let number = if negative { -number } else { number };
+    i64::try_from(number).ok()
+}
+
+/// Convert a cell to the value to store in a column of the given type.
+/// Empty cells in numeric columns become NULL. Returns None if the value can't be read as the type, leaving the
+/// caller to decide what to do about it with --on-type-error.
+/// Numbers are stored without any currency symbols stripped by --strip-currency.
+pub fn to_sql_value(value: &str, column_type: ColumnType, args: &Arguments) -> Option<Value> {
+    match column_type {
+        ColumnType::Text => Some(Value::Text(value.to_string())),
+        _ if value.is_empty() => Some(Value::Null),
+        ColumnType::Integer => parse_integer(value, args).map(Value::Integer),
+        ColumnType::Real => parse_real(value, args).map(Value::Real),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thousands_separators() {
+        assert_eq!(strip_thousands_separators("1,234"), Some("1234".to_string()));
+        assert_eq!(strip_thousands_separators("-1,234,567.89"), Some("-1234567.89".to_string()));
+        assert_eq!(strip_thousands_separators("123"), Some("123".to_string()));
+        assert_eq!(strip_thousands_separators("1,2"), None);
+        assert_eq!(strip_thousands_separators("1,2345"), None);
+        assert_eq!(strip_thousands_separators("1234,567"), None);
+        assert_eq!(strip_thousands_separators(",123"), None);
+        assert_eq!(strip_thousands_separators("1.234,56"), None);
+        assert_eq!(strip_thousands_separators("a,bcd"), None);
+    }
+
+    #[test]
+    fn scientific_integers() {
+        assert_eq!(parse_scientific_integer("1e3"), Some(1000));
+        assert_eq!(parse_scientific_integer("1.5E3"), Some(1500));
+        assert_eq!(parse_scientific_integer("-2.50e2"), Some(-250));
+        assert_eq!(parse_scientific_integer("1500e-2"), Some(15));
+        assert_eq!(parse_scientific_integer("1.5e0"), None);
+        assert_eq!(parse_scientific_integer("9.223372036854775807e18"), Some(i64::MAX));
+        assert_eq!(parse_scientific_integer("-9.223372036854775808e18"), Some(i64::MIN));
+        assert_eq!(parse_scientific_integer("9.223372036854775808e18"), None);
+    }
+
+    #[test]
+    fn scientific_integers_with_extreme_exponents() {
+        assert_eq!(parse_scientific_integer("1.5e-2147483648"), None);
+        assert_eq!(parse_scientific_integer("1e2147483647"), None);
+        assert_eq!(parse_scientific_integer("1e20"), None);
+        assert_eq!(parse_scientific_integer("0e2147483647"), Some(0));
+        assert_eq!(parse_scientific_integer("0.000e-2147483648"), Some(0));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::*;
+use regex::Regex;
+use std::str::FromStr;
+
+/// A pattern every value in a column has to match, given with --validate as `COLUMN:REGEX`.
+/// The regex can match anywhere in the value, so anchor it with ^ and $ to check the whole thing.
+#[derive(Debug, Clone)]
+pub struct Validation {
+    pub column: String,
+    pub regex: Regex,
+}
+
+impl FromStr for Validation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Regexes are much more likely to contain colons than column names are.
+        let (column, pattern) = s.split_once(':')
+            .ok_or_else(|| format!("expected 'COLUMN:REGEX' but got '{}'", s))?;
+        if column.is_empty() {
+            return Err(format!("no column name in '{}'", s));
+        }
+        let regex = Regex::new(pattern).map_err(|er| er.to_string())?;
+        Ok(Validation { column: column.to_string(), regex })
+    }
+}
+
+/// Check every row against the --validate patterns, warning about each value that doesn't match.
+/// Empty values aren't checked. With --strict, the first value that doesn't match is an error instead.
+/// Returns the number of values which didn't match.
+pub fn validate_rows(header: &[&str], rows: &[Vec<String>], cached_csv: &CSVCache, args: &Arguments) -> Result<usize, ImportError> {
+    let validations = column_validations(header, args)?;
+    let mut failures = 0;
+    for (row_number, row) in rows.iter().enumerate() {
+        failures += validate_row(&validations, row_number as u64 + 1, cached_csv.row_line(row_number), row, args)?;
+    }
+    Ok(failures)
+}
+
+/// Find the column each --validate pattern is for.
+pub fn column_validations<'a>(header: &[&str], args: &'a Arguments) -> Result<Vec<(usize, &'a Validation)>, ImportError> {
+    let mut validations = vec![];
+    for validation in &args.validations {
+        let index = header.iter()
+            .position(|h| *h == validation.column)
+            .ok_or_else(|| ImportError::Other(format!("Validation column '{}' doesn't exist!", validation.column)))?;
+        validations.push((index, validation));
+    }
+    Ok(validations)
+}
+
+/// Check one row against the --validate patterns, as `validate_rows` does, returning the number of values which
+/// didn't match. `row` counts from 1.
+pub fn validate_row(validations: &[(usize, &Validation)], row: u64, line: Option<u64>, values: &[String], args: &Arguments) -> Result<usize, ImportError> {
+    let mut failures = 0;
+    for (index, validation) in validations {
+        let value = values.get(*index).map_or("", |value| value.as_str());
+        if value.is_empty() || validation.regex.is_match(value) {
+            continue;
+        }
+
+        // Values are checked before they're redacted, but a redacted one still mustn't be shown.
+        let shown = match redaction_for(&validation.column, args) {
+            Some(mode) => redact(mode, value, args),
+            None => value.to_string(),
+        };
+        let message = format!(
+            "row #{} (line {}): '{}' in column '{}' doesn't match /{}/",
+            row, line.unwrap_or(0), shown, validation.column, validation.regex,
+        );
+        if args.strict {
+            return Err(ImportError::Other(message));
+        }
+        let at = RowLocation {
+            row: Some(row),
+            line,
+            column: Some(&validation.column),
+        };
+        row_warning(args, ErrorCategory::Validation, at, format_args!("{}", message));
+        failures += 1;
+    }
+    Ok(failures)
+}
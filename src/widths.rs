@@ -0,0 +1,76 @@
+use crate::*;
+use std::collections::BTreeMap;
+
+/// How many of the most common widths to show for each column.
+const COMMON_WIDTHS: usize = 5;
+
+/// Print how wide the fields of a fixed-width file are, for --column-width-report.
+/// Character positions which are blank on every line are taken to separate the columns, and each column runs up to
+/// where the next one starts, since fields are usually padded out to their width. For each column, the widths of its
+/// values (without the padding) are counted, and the report ends with the column widths as a comma-separated list.
+/// Blank lines and lines starting with '#' are ignored.
+pub fn column_width_report(args: &Arguments, path: &Path) -> Result<(), ImportError> {
+    let contents = if path == Path::new(STDIN) {
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin().lock(), &mut contents)?;
+        contents
+    } else {
+        std::fs::read_to_string(path)?
+    };
+    let lines = contents.lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .collect::<Vec<Vec<char>>>();
+
+    report(args, format_args!("{}: {} lines", path.display(), lines.len()));
+    let columns = column_spans(&lines);
+    if columns.is_empty() {
+        report(args, format_args!("  nothing to report"));
+        return Ok(());
+    }
+
+    for (ii, &(start, end)) in columns.iter().enumerate() {
+        let mut widths: BTreeMap<usize, usize> = BTreeMap::new();
+        for line in &lines {
+            let field = line.get(start..end.min(line.len())).unwrap_or_default();
+            let width = field.iter().collect::<String>().trim().chars().count();
+            *widths.entry(width).or_default() += 1;
+        }
+
+        let mut common = widths.iter().map(|(&width, &count)| (width, count)).collect::<Vec<(usize, usize)>>();
+        common.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let common = common.iter()
+            .take(COMMON_WIDTHS)
+            .map(|&(width, count)| format!("{} ({} line{})", width, count, if count == 1 { "" } else { "s" }))
+            .collect::<Vec<String>>();
+        let (narrowest, widest) = (widths.keys().next().unwrap(), widths.keys().next_back().unwrap());
+        report(args, format_args!("  column {}: positions {}-{} ({} wide); values {} to {} wide, most often {}",
+            ii + 1, start + 1, end, end - start, narrowest, widest, common.join(", ")));
+    }
+
+    let widths = columns.iter().map(|(start, end)| (end - start).to_string()).collect::<Vec<String>>();
+    report(args, format_args!("  column widths: {}", widths.join(",")));
+    Ok(())
+}
+
+/// Find where each column starts and ends, as character positions (end exclusive).
+/// A column starts wherever a position with something in it on some line follows one which is blank on every line.
+fn column_spans(lines: &[Vec<char>]) -> Vec<(usize, usize)> {
+    let longest = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    let blank = (0..longest)
+        .map(|pos| lines.iter().all(|line| line.get(pos).is_none_or(|c| c.is_whitespace())))
+        .collect::<Vec<bool>>();
+
+    // Anything before the first column is padding, so it belongs to that column.
+    let mut starts = (0..longest)
+        .filter(|&pos| !blank[pos] && (pos == 0 || blank[pos - 1]))
+        .collect::<Vec<usize>>();
+    if let Some(first) = starts.first_mut() {
+        *first = 0;
+    }
+    starts.iter()
+        .enumerate()
+        .map(|(ii, &start)| (start, starts.get(ii + 1).copied().unwrap_or(longest)))
+        .collect()
+}
@@ -0,0 +1,30 @@
+mod common;
+use common::*;
+
+#[test]
+fn adds_the_new_columns_when_appending() {
+    let first = write_temp("people.csv", "name,age\nann,30\n");
+    let database = temp_path("out.db");
+    let output = run(&[first.to_str().unwrap(), "--output", database.to_str().unwrap(), "--infer-types"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let second = write_temp("people.csv", "name,age,city\nbob,40,Leeds\n");
+    let output = run(&[second.to_str().unwrap(), "--output", database.to_str().unwrap(), "--infer-types", "--append", "--add-missing-columns"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(column_types(&conn, "people"), columns(&[("name", "TEXT"), ("age", "INTEGER"), ("city", "TEXT")]));
+    assert_eq!(query(&conn, "SELECT name, age, city FROM people ORDER BY rowid"), ["ann|30|NULL", "bob|40|Leeds"]);
+}
+
+#[test]
+fn leaves_out_rows_with_new_columns_without_it() {
+    let first = write_temp("people.csv", "name\nann\n");
+    let database = temp_path("out.db");
+    assert!(run(&[first.to_str().unwrap(), "--output", database.to_str().unwrap()]).status.success());
+
+    let second = write_temp("people.csv", "name,city\nbob,Leeds\n");
+    let output = run(&[second.to_str().unwrap(), "--output", database.to_str().unwrap(), "--append"]);
+    assert!(stderr(&output).contains("no column named city"), "{}", stderr(&output));
+    assert_eq!(row_count(&rusqlite::Connection::open(&database).unwrap(), "people"), 1);
+}
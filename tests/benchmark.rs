@@ -0,0 +1,16 @@
+mod common;
+use common::*;
+
+#[test]
+fn reports_the_throughput() {
+    let output = run(&["--benchmark", "100", "3"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let report = stdout(&output);
+    assert!(report.contains("100 rows of 3 columns"), "{}", report);
+    assert!(report.contains("rows/sec") && report.contains("MB/sec"), "{}", report);
+}
+
+#[test]
+fn needs_rows_and_columns() {
+    assert!(!run(&["--benchmark", "100"]).status.success());
+}
@@ -0,0 +1,29 @@
+mod common;
+use common::*;
+
+#[test]
+fn records_the_sha256_of_the_source() {
+    let input = write_temp("numbers.csv", "n\n1\n2\n");
+    let conn = import(&input, &["--verify-checksum"]);
+    assert_eq!(
+        query(&conn, "SELECT table_name, checksum FROM _import_metadata"),
+        ["numbers|b08a11a12effc9d6330979a26f9cc9ed89e5cbb45bfbdbf97e2a78fdaf66cfe9"],
+    );
+}
+
+#[test]
+fn skips_a_file_which_hasnt_changed() {
+    let input = write_temp("numbers.csv", "n\n1\n2\n");
+    let database = temp_path("out.db");
+    let args = [input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--skip-if-unchanged"];
+    for _ in 0..2 {
+        assert!(run(&args).status.success());
+    }
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(row_count(&conn, "numbers"), 2);
+
+    // Once it changes, it's imported again.
+    std::fs::write(&input, "n\n1\n2\n3\n").unwrap();
+    assert!(run(&args).status.success());
+    assert_eq!(row_count(&conn, "numbers"), 5);
+}
@@ -0,0 +1,13 @@
+mod common;
+use common::*;
+
+#[test]
+fn zero_width_characters_are_cleaned_from_headers() {
+    let conn = import_fixture("zero_width_header.csv", &["--clean-headers"]);
+    assert_eq!(row_count(&conn, "zero_width_header"), 2);
+    assert_eq!(column_types(&conn, "zero_width_header"), columns(&[("name", "TEXT"), ("city", "TEXT"), ("zip", "TEXT")]));
+
+    // Without --clean-headers, the zero-width space stays in the name.
+    let conn = import_fixture("zero_width_header.csv", &[]);
+    assert_eq!(column_types(&conn, "zero_width_header"), columns(&[("name", "TEXT"), ("\u{200b}city", "TEXT"), ("zip", "TEXT")]));
+}
@@ -0,0 +1,21 @@
+mod common;
+use common::*;
+
+#[test]
+fn gives_columns_their_collation() {
+    let input = write_temp("places.csv", "country,code\nUSA,a \nusa,b\n");
+    let conn = import(&input, &["--collate", "country:nocase", "--collate", "code:RTRIM"]);
+    assert_eq!(query(&conn, "SELECT COUNT(*) FROM places WHERE country = 'Usa'"), ["2"]);
+    assert_eq!(query(&conn, "SELECT country FROM places WHERE code = 'a'"), ["USA"]);
+    let schema = query(&conn, "SELECT sql FROM sqlite_master WHERE name = 'places'").join("");
+    assert!(schema.contains("COLLATE NOCASE") && schema.contains("COLLATE RTRIM"), "{}", schema);
+}
+
+#[test]
+fn rejects_unknown_collations() {
+    let input = write_temp("places.csv", "country\nUSA\n");
+    for collation in ["country:FRENCH", "country", "city:NOCASE"] {
+        let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--collate", collation]);
+        assert!(!output.status.success(), "{} was accepted", collation);
+    }
+}
@@ -0,0 +1,28 @@
+mod common;
+use common::*;
+
+#[test]
+fn rejects_too_few_columns() {
+    let input = write_temp("people.csv", "name;age\nann;30\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--input-format", "csv", "--min-columns", "2"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("--min-columns"), "{}", stderr(&output));
+
+    let conn = import(&input, &["--input-format", "csv", "--delimiter", ";", "--min-columns", "2"]);
+    assert_eq!(row_count(&conn, "people"), 1);
+}
+
+#[test]
+fn rejects_too_many_columns() {
+    let input = write_temp("people.csv", "a,b,c\n1,2,3\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--max-columns", "2"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("--max-columns"), "{}", stderr(&output));
+}
+
+#[test]
+fn counts_the_first_row_without_a_header() {
+    let input = write_temp("people.csv", "ann\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--disable-header", "--min-columns", "2"]);
+    assert!(!output.status.success());
+}
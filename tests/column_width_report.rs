@@ -0,0 +1,16 @@
+mod common;
+use common::*;
+
+#[test]
+fn reports_the_width_of_each_column() {
+    let input = write_temp("report.txt", "name      age  city\nann       30   Leeds\nbobby     4    York\n");
+    let output = run(&[input.to_str().unwrap(), "--column-width-report"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!input.with_extension("db").exists());
+
+    let report = stdout(&output);
+    assert!(report.contains("3 lines"), "{}", report);
+    assert!(report.contains("column 1: positions 1-10 (10 wide); values 3 to 5 wide"), "{}", report);
+    assert!(report.contains("column 3: positions 16-20 (5 wide); values 4 to 5 wide, most often 4 (2 lines)"), "{}", report);
+    assert!(report.ends_with("column widths: 10,5,5\n"), "{}", report);
+}
@@ -0,0 +1,140 @@
+//! Helpers for the integration tests, which run the command line tool the way a user would.
+#![allow(dead_code)]
+
+use rusqlite::{types::Value, Connection};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The path of one of the files in fixtures/.
+pub fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures").join(name)
+}
+
+/// A path called `name` in a temporary directory of its own, so tests running at the same time don't share files.
+/// Nothing is there yet.
+pub fn temp_path(name: &str) -> PathBuf {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!("csv_to_sqlite-test-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed)));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+/// Write a file called `name` into a temporary directory, returning its path.
+pub fn write_temp(name: &str, contents: &str) -> PathBuf {
+    let path = temp_path(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Run the tool with some arguments, with warnings logged, and nothing on stdin.
+pub fn run(args: &[&str]) -> Output {
+    run_with_input(args, "")
+}
+
+/// Run the tool with some arguments, with warnings logged, giving it `input` on stdin.
+pub fn run_with_input(args: &[&str], input: &str) -> Output {
+    run_logging(args, input, "warn")
+}
+
+/// Run the tool with some arguments and RUST_LOG set to `level`, giving it `input` on stdin.
+pub fn run_logging(args: &[&str], input: &str, level: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_to_sqlite"))
+        .args(args)
+        .env("RUST_LOG", level)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The tool may exit without reading all of it.
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+/// What the tool logged.
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+/// What the tool wrote to stdout.
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Import a file into a fresh database, with some extra arguments, and open the database.
+/// The import has to succeed. The table is named after the file, as usual.
+pub fn import(input: &Path, extra: &[&str]) -> Connection {
+    let database = temp_path("out.db");
+    let output = run(&[&[input.to_str().unwrap(), "--output", database.to_str().unwrap()], extra].concat());
+    assert!(output.status.success(), "importing {} with {:?} failed:\n{}", input.display(), extra, stderr(&output));
+    Connection::open(&database).unwrap()
+}
+
+/// Import one of the fixtures, as `import` does.
+pub fn import_fixture(name: &str, extra: &[&str]) -> Connection {
+    import(&fixture(name), extra)
+}
+
+/// The rows a query returns, each as its values joined with "|", and NULL shown as "NULL".
+pub fn query(conn: &Connection, sql: &str) -> Vec<String> {
+    let mut stmt = conn.prepare(sql).unwrap();
+    let columns = stmt.column_count();
+    let rows = stmt.query_map([], |row| {
+        (0..columns)
+            .map(|ii| Ok(match row.get::<_, Value>(ii)? {
+                Value::Null => "NULL".to_string(),
+                Value::Integer(number) => number.to_string(),
+                Value::Real(number) => number.to_string(),
+                Value::Text(text) => text,
+                Value::Blob(blob) => format!("{:?}", blob),
+            }))
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .map(|values| values.join("|"))
+    });
+    rows.unwrap().collect::<rusqlite::Result<Vec<String>>>().unwrap()
+}
+
+/// Quote a table or column name for SQL.
+pub fn quote(name: &str) -> String {
+    format!(r#""{}""#, name.replace('"', r#""""#))
+}
+
+/// How many rows a table has.
+pub fn row_count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {};", quote(table)), [], |r| r.get(0)).unwrap()
+}
+
+/// The names and declared types of a table's columns, leaving out the automatic "id" column.
+pub fn column_types(conn: &Connection, table: &str) -> Vec<(String, String)> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({});", quote(table))).unwrap();
+    let columns = stmt.query_map([], |r| Ok((r.get::<_, String>(1)?, r.get::<_, String>(2)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<Vec<(String, String)>>>()
+        .unwrap();
+    columns.into_iter().filter(|(name, _)| name != "id").collect()
+}
+
+/// Pairs of column names and types, as column_types gives them.
+pub fn columns(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected.iter().map(|(name, column_type)| (name.to_string(), column_type.to_string())).collect()
+}
+
+/// One column of a table, as text, in the order the rows went in.
+pub fn column_values(conn: &Connection, table: &str, column: &str) -> Vec<Option<String>> {
+    let mut stmt = conn.prepare(&format!("SELECT CAST({} AS TEXT) FROM {} ORDER BY rowid;", quote(column), quote(table))).unwrap();
+    let values = stmt.query_map([], |r| r.get(0))
+        .unwrap()
+        .collect::<rusqlite::Result<Vec<Option<String>>>>()
+        .unwrap();
+    values
+}
+
+/// Values as column_values gives them.
+pub fn values(expected: &[Option<&str>]) -> Vec<Option<String>> {
+    expected.iter().map(|value| value.map(String::from)).collect()
+}
@@ -0,0 +1,19 @@
+mod common;
+use common::*;
+
+#[test]
+fn uses_small_pages() {
+    let input = write_temp("numbers.csv", "n,word\n1,one\n2,two\n3,three\n");
+    let conn = import(&input, &["--compact"]);
+    let page_size: i64 = conn.query_row("PRAGMA page_size;", [], |r| r.get(0)).unwrap();
+    assert_eq!(page_size, 1024);
+    assert_eq!(query(&conn, "SELECT n, word FROM numbers ORDER BY rowid"), ["1|one", "2|two", "3|three"]);
+}
+
+#[test]
+fn leaves_the_default_page_size_alone_otherwise() {
+    let input = write_temp("numbers.csv", "n\n1\n");
+    let conn = import(&input, &[]);
+    let page_size: i64 = conn.query_row("PRAGMA page_size;", [], |r| r.get(0)).unwrap();
+    assert_eq!(page_size, 4096);
+}
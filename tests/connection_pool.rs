@@ -0,0 +1,22 @@
+mod common;
+use common::*;
+use rusqlite::Connection;
+
+#[test]
+fn imports_every_file_into_one_database() {
+    // Enough rows that each table is committed several times.
+    let rows = (1..=2500).map(|n| format!("{},row {}\n", n, n)).collect::<String>();
+    let inputs = ["first.csv", "second.csv", "third.csv", "fourth.csv"]
+        .map(|name| write_temp(name, &format!("n,label\n{}", rows)));
+    let database = temp_path("out.db");
+    let mut args = inputs.iter().map(|input| input.to_str().unwrap()).collect::<Vec<&str>>();
+    args.extend(["--output", database.to_str().unwrap(), "--connection-pool", "3"]);
+    let output = run(&args);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let conn = Connection::open(&database).unwrap();
+    for table in ["first", "second", "third", "fourth"] {
+        assert_eq!(row_count(&conn, table), 2500);
+        assert_eq!(query(&conn, &format!("SELECT label FROM {} WHERE n = 2500", table)), ["row 2500"]);
+    }
+}
@@ -0,0 +1,38 @@
+mod common;
+use common::*;
+
+/// Two tables which join on the customer id.
+fn inputs() -> (String, String) {
+    let customers = write_temp("customers.csv", "customer_id,name\n1,ann\n2,bob\n");
+    let orders = customers.parent().unwrap().join("orders.csv");
+    std::fs::write(&orders, "customer,total\n1,10\n2,20\n1,5\n").unwrap();
+    (customers.to_str().unwrap().to_string(), orders.to_str().unwrap().to_string())
+}
+
+#[test]
+fn creates_the_view_after_importing() {
+    let (customers, orders) = inputs();
+    let database = temp_path("out.db");
+    let view = "spend AS SELECT name, SUM(total) AS total FROM customers JOIN orders ON orders.customer = customers.customer_id GROUP BY name";
+    let output = run(&[&customers, &orders, "--output", database.to_str().unwrap(), "--infer-types", "--create-view", view]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(query(&conn, "SELECT name, total FROM spend ORDER BY name"), ["ann|15", "bob|20"]);
+}
+
+#[test]
+fn fails_on_a_view_of_a_missing_table() {
+    let (customers, orders) = inputs();
+    let database = temp_path("out.db");
+    let output = run(&[&customers, &orders, "--output", database.to_str().unwrap(), "--create-view", "spend AS SELECT * FROM invoices"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("invoices"), "{}", stderr(&output));
+}
+
+#[test]
+fn rejects_views_without_a_select() {
+    let (customers, _) = inputs();
+    let output = run(&[&customers, "--output", temp_path("out.db").to_str().unwrap(), "--create-view", "spend"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,44 @@
+mod common;
+use common::*;
+
+#[test]
+fn drops_duplicates_and_reports_them() {
+    let input = write_temp("people.csv", "name,age\nann,30\nbob,40\nann,30\nann,31\n");
+    let report = temp_path("report.csv");
+    let conn = import(&input, &["--dedup", "--dedup-report", report.to_str().unwrap()]);
+    assert_eq!(query(&conn, "SELECT name, age FROM people ORDER BY rowid"), ["ann|30", "bob|40", "ann|31"]);
+    assert!(std::fs::read_to_string(&report).unwrap().ends_with("4,ann,30\n"));
+}
+
+#[test]
+fn compares_on_the_key_columns() {
+    let input = write_temp("people.csv", "name,age\nann,30\nbob,40\nann,31\n");
+    let conn = import(&input, &["--dedup", "--dedup-key", "name"]);
+    assert_eq!(query(&conn, "SELECT name, age FROM people ORDER BY rowid"), ["ann|30", "bob|40"]);
+}
+
+#[test]
+fn finds_key_columns_by_their_default_names_without_a_header() {
+    let input = write_temp("letters.csv", "a,1\nb,2\na,3\n");
+    let report = temp_path("report.csv");
+    let conn = import(&input, &["--disable-header", "--dedup", "--dedup-key", "column1", "--dedup-report", report.to_str().unwrap()]);
+    assert_eq!(query(&conn, "SELECT column1, column2 FROM letters ORDER BY rowid"), ["a|1", "b|2"]);
+    assert!(std::fs::read_to_string(&report).unwrap().ends_with("3,a\n"));
+}
+
+#[test]
+fn fails_on_a_missing_key_column() {
+    let input = write_temp("letters.csv", "a,1\nb,2\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--disable-header", "--dedup", "--dedup-key", "column3"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("doesn't exist"), "{}", stderr(&output));
+}
+
+#[test]
+fn fails_when_the_report_cant_be_written() {
+    let input = write_temp("people.csv", "name\nann\nann\n");
+    let report = temp_path("missing").join("report.csv");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--dedup", "--dedup-report", report.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("dedup report"), "{}", stderr(&output));
+}
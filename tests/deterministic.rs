@@ -0,0 +1,22 @@
+mod common;
+use common::*;
+
+#[test]
+fn pins_the_date_to_the_epoch() {
+    let input = write_temp("sales.csv", "n\n1\n");
+    let conn = import(&input, &["--deterministic", "--table-name-pattern", "{basename}_{date}"]);
+    assert_eq!(row_count(&conn, "sales_19700101"), 1);
+}
+
+#[test]
+fn can_be_set_from_the_environment() {
+    let input = write_temp("sales.csv", "n\n1\n");
+    let database = temp_path("out.db");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_csv_to_sqlite"))
+        .args([input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--table-name-pattern", "{basename}_{date}"])
+        .env("CSV_TO_SQLITE_DETERMINISTIC", "true")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(row_count(&rusqlite::Connection::open(&database).unwrap(), "sales_19700101"), 1);
+}
@@ -0,0 +1,21 @@
+mod common;
+use common::*;
+
+#[test]
+fn shows_the_lines_around_a_bad_row() {
+    let input = temp_path("numbers.csv");
+    std::fs::write(&input, b"n\n1\n2\n\xff\n4\n5\n").unwrap();
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--error-context", "1"]);
+    let logged = stderr(&output);
+    assert!(logged.contains("around line 4 of"), "{}", logged);
+    assert!(logged.contains("      3 | 2") && logged.contains(">      4 |") && logged.contains("      5 | 4"), "{}", logged);
+    assert!(!logged.contains("| 1\n") && !logged.contains("| 5\n"), "{}", logged);
+}
+
+#[test]
+fn shows_nothing_by_default() {
+    let input = temp_path("numbers.csv");
+    std::fs::write(&input, b"n\n1\n\xff\n").unwrap();
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap()]);
+    assert!(!stderr(&output).contains("around line"), "{}", stderr(&output));
+}
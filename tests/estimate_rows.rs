@@ -0,0 +1,19 @@
+mod common;
+use common::*;
+
+#[test]
+fn progress_shows_the_estimated_total() {
+    let input = write_temp("numbers.csv", "n\n1\n2\n3\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--progress", "--estimate-rows"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Reading: 1/3 rows"), "{}", stderr(&output));
+}
+
+#[test]
+fn progress_has_no_total_without_an_estimate() {
+    let input = write_temp("numbers.csv", "n\n1\n2\n3\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--progress"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stderr(&output).contains("Reading: 1/3 rows"), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Reading: 1 rows"), "{}", stderr(&output));
+}
@@ -0,0 +1,25 @@
+mod common;
+use common::*;
+
+#[test]
+fn shows_the_plan_without_writing_anything() {
+    let input = write_temp("people.csv", "x.name,x.age\nann,30\nbob,4.5\n");
+    let database = temp_path("out.db");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--dry-run", "--explain", "--infer-types", "--header-strip-prefix", "x."]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!database.exists());
+
+    let plan = stdout(&output);
+    assert!(plan.contains("Table 'people': 2 rows, 2 columns"), "{}", plan);
+    assert!(plan.contains(r#"header "x.age" renamed to "age""#), "{}", plan);
+    assert!(plan.contains(r#""age" REAL (inferred from '4.5')"#), "{}", plan);
+    assert!(plan.contains(r#"CREATE TABLE IF NOT EXISTS "people""#), "{}", plan);
+    assert!(plan.contains(r#"INSERT INTO "people" ("name", "age") VALUES (?, ?);"#), "{}", plan);
+}
+
+#[test]
+fn points_out_repeated_columns() {
+    let input = write_temp("people.csv", "name,age,age\nann,30,31\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--dry-run", "--explain"]);
+    assert!(stdout(&output).contains(r#"column "age" appears 2 times"#), "{}", stdout(&output));
+}
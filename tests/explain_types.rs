@@ -0,0 +1,29 @@
+mod common;
+use common::*;
+
+#[test]
+fn shows_the_evidence_for_each_type() {
+    let input = write_temp("values.csv", "a,b,c\n1,2,x\n2,2.5,\n,3,4\n");
+    let database = temp_path("out.db");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--explain-types", "--infer-types"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let report = stdout(&output);
+    assert!(report.contains("Types for table 'values':"), "{}", report);
+    assert!(report.contains(r#""a" INTEGER (inferred from '1'): 2 integer, 0 real, 0 text, 1 null"#), "{}", report);
+    assert!(report.contains(r#""b" REAL (inferred from '2.5'): 2 integer, 1 real, 0 text, 0 null"#), "{}", report);
+    assert!(report.contains("widened to REAL by '2.5' in row #2"), "{}", report);
+    assert!(report.contains("widened to TEXT by 'x' in row #1"), "{}", report);
+
+    // The import still happens.
+    assert_eq!(row_count(&rusqlite::Connection::open(&database).unwrap(), "values"), 3);
+}
+
+#[test]
+fn works_with_dry_run() {
+    let input = write_temp("values.csv", "a\n1\n");
+    let database = temp_path("out.db");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--explain-types", "--dry-run"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(r#""a" TEXT (--infer-types isn't set)"#), "{}", stdout(&output));
+    assert!(!database.exists());
+}
@@ -0,0 +1,29 @@
+mod common;
+use common::*;
+
+/// A file which doesn't exist, followed by one which imports fine.
+fn inputs() -> (String, String) {
+    let good = write_temp("good.csv", "n\n1\n");
+    let missing = good.parent().unwrap().join("missing.csv");
+    (missing.to_str().unwrap().to_string(), good.to_str().unwrap().to_string())
+}
+
+#[test]
+fn carries_on_after_a_failure_by_default() {
+    let (missing, good) = inputs();
+    let database = temp_path("out.db");
+    let output = run(&[&missing, &good, "--output", database.to_str().unwrap()]);
+    assert!(!output.status.success());
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(row_count(&conn, "good"), 1);
+}
+
+#[test]
+fn stops_at_the_first_failure() {
+    let (missing, good) = inputs();
+    let database = temp_path("out.db");
+    let output = run(&[&missing, &good, "--output", database.to_str().unwrap(), "--fail-fast"]);
+    assert!(!output.status.success());
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert!(query(&conn, "SELECT name FROM sqlite_master WHERE name = 'good'").is_empty());
+}
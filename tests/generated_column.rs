@@ -0,0 +1,20 @@
+mod common;
+use common::*;
+
+#[test]
+fn adds_computed_columns() {
+    let input = write_temp("people.csv", "first,last,age\nAnn,Lee,30\n");
+    let conn = import(&input, &["--infer-types", "--generated-column", "full_name = first || ' ' || last", "--generated-column", "months = age * 12 STORED"]);
+    assert_eq!(query(&conn, "SELECT full_name, months FROM people"), ["Ann Lee|360"]);
+    let schema = query(&conn, "SELECT sql FROM sqlite_master WHERE name = 'people'").join("");
+    assert!(schema.contains("STORED"), "{}", schema);
+}
+
+#[test]
+fn rejects_bad_generated_columns() {
+    let input = write_temp("people.csv", "first,last\nAnn,Lee\n");
+    for generated in ["full_name = first ||| last", "full_name = middle", "first = last", "full_name"] {
+        let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--generated-column", generated]);
+        assert!(!output.status.success(), "{} was accepted", generated);
+    }
+}
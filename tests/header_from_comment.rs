@@ -0,0 +1,10 @@
+mod common;
+use common::*;
+
+#[test]
+fn header_is_read_from_a_comment() {
+    let conn = import_fixture("header_comment.csv", &["--header-from-comment"]);
+    assert_eq!(row_count(&conn, "header_comment"), 2);
+    assert_eq!(column_types(&conn, "header_comment"), columns(&[("name", "TEXT"), ("city", "TEXT"), ("zip", "TEXT")]));
+    assert_eq!(column_values(&conn, "header_comment", "name"), values(&[Some("Ada"), Some("Grace")]));
+}
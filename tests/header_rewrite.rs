@@ -0,0 +1,23 @@
+mod common;
+use common::*;
+
+#[test]
+fn rewrites_the_header_names() {
+    let input = write_temp("orders.csv", "orders.code_col,orders.unit price_col,total\n1,2,3\n");
+    let conn = import(&input, &["--header-strip-prefix", "orders.", "--header-strip-suffix", "_col", "--header-replace", " :_"]);
+    assert_eq!(column_types(&conn, "orders"), columns(&[("code", "TEXT"), ("unit_price", "TEXT"), ("total", "TEXT")]));
+}
+
+#[test]
+fn replacements_run_in_order() {
+    let input = write_temp("orders.csv", "a-b\n1\n");
+    let conn = import(&input, &["--header-replace=-:_", "--header-replace=a_:x"]);
+    assert_eq!(column_types(&conn, "orders"), columns(&[("xb", "TEXT")]));
+}
+
+#[test]
+fn replacements_can_remove_text() {
+    let input = write_temp("orders.csv", "unit (GBP)\n1\n");
+    let conn = import(&input, &["--header-replace", " (GBP):"]);
+    assert_eq!(column_types(&conn, "orders"), columns(&[("unit", "TEXT")]));
+}
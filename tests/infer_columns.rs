@@ -0,0 +1,20 @@
+mod common;
+use common::*;
+
+#[test]
+fn infers_only_the_named_columns() {
+    let input = write_temp("items.csv", "a,b,c\n1,2.5,3\n4,5,6\n");
+    let conn = import(&input, &["--infer-types", "--infer-columns", "a,b"]);
+    assert_eq!(column_types(&conn, "items"), columns(&[("a", "INTEGER"), ("b", "REAL"), ("c", "TEXT")]));
+
+    let conn = import(&input, &["--infer-types", "--infer-columns", "c", "--infer-columns", "b"]);
+    assert_eq!(column_types(&conn, "items"), columns(&[("a", "TEXT"), ("b", "REAL"), ("c", "INTEGER")]));
+}
+
+#[test]
+fn fails_on_a_missing_column() {
+    let input = write_temp("items.csv", "a\n1\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--infer-types", "--infer-columns", "z"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("'z'"), "{}", stderr(&output));
+}
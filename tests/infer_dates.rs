@@ -0,0 +1,29 @@
+mod common;
+use common::*;
+
+#[test]
+fn stores_dates_in_iso_form() {
+    let input = write_temp("events.csv", "start,end,note\n2024-03-01,03/01/2024,x\n2024-3-2,12/31/2023,2024-01-01\n2024/03/05,01/02/2024,y\n");
+    let database = temp_path("out.db");
+    let output = run_logging(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--infer-types", "--infer-dates"], "", "info");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let logged = stderr(&output);
+    assert!(logged.contains("column 'start' holds dates in YYYY-MM-DD format"), "{}", logged);
+    assert!(logged.contains("column 'end' holds dates in MM/DD/YYYY format"), "{}", logged);
+    assert!(logged.contains("'2024/03/05'") && !logged.contains("column 'note' holds dates"), "{}", logged);
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(column_types(&conn, "events"), columns(&[("start", "TEXT"), ("end", "TEXT"), ("note", "TEXT")]));
+    assert_eq!(query(&conn, "SELECT start, \"end\", note FROM events ORDER BY rowid"), [
+        "2024-03-01|2024-03-01|x",
+        "2024-03-02|2023-12-31|2024-01-01",
+        "2024/03/05|2024-01-02|y",
+    ]);
+}
+
+#[test]
+fn leaves_dates_alone_without_it() {
+    let input = write_temp("events.csv", "start\n03/01/2024\n");
+    let conn = import(&input, &["--infer-types"]);
+    assert_eq!(column_values(&conn, "events", "start"), values(&[Some("03/01/2024")]));
+}
@@ -0,0 +1,35 @@
+mod common;
+use common::*;
+
+#[test]
+fn works_out_the_delimiter() {
+    for (delimiter, name) in [(';', "semicolons.txt"), ('\t', "tabs.csv"), ('|', "pipes.dat")] {
+        let input = write_temp(name, &format!("full name{0}age\nann lee{0}30\nbob{0}40\n", delimiter));
+        let conn = import(&input, &[]);
+        let table = name.split('.').next().unwrap();
+        assert_eq!(column_types(&conn, table), columns(&[("full name", "TEXT"), ("age", "TEXT")]), "{:?}", delimiter);
+    }
+}
+
+#[test]
+fn rejects_json_and_gzip() {
+    let json = write_temp("json.csv", "[{\"a\": 1}]\n");
+    let output = run(&[json.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("JSON"), "{}", stderr(&output));
+
+    let gzip = temp_path("gzip.csv");
+    std::fs::write(&gzip, b"\x1f\x8b\x08\x00rest").unwrap();
+    let output = run(&[gzip.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("gzip"), "{}", stderr(&output));
+}
+
+#[test]
+fn can_be_given() {
+    let input = write_temp("people.csv", "name\tcity,age\nann\tx,30\n");
+    let conn = import(&input, &["--input-format", "csv"]);
+    assert_eq!(column_types(&conn, "people"), columns(&[("name\tcity", "TEXT"), ("age", "TEXT")]));
+    let conn = import(&input, &["--input-format", "tsv"]);
+    assert_eq!(column_types(&conn, "people"), columns(&[("name", "TEXT"), ("city,age", "TEXT")]));
+}
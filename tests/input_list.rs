@@ -0,0 +1,24 @@
+mod common;
+use common::*;
+
+#[test]
+fn imports_the_listed_files() {
+    let list = write_temp("inputs.txt", "# The monthly exports\nmarch.csv\n\napril.csv\tspring\n");
+    let dir = list.parent().unwrap();
+    std::fs::write(dir.join("march.csv"), "day,sales\n1,10\n2,20\n").unwrap();
+    std::fs::write(dir.join("april.csv"), "day,sales\n1,30\n").unwrap();
+    let database = temp_path("out.db");
+    let output = run(&["--input-list", list.to_str().unwrap(), "--output", database.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(query(&conn, "SELECT day, sales FROM march ORDER BY rowid"), ["1|10", "2|20"]);
+    assert_eq!(query(&conn, "SELECT day, sales FROM spring ORDER BY rowid"), ["1|30"]);
+}
+
+#[test]
+fn fails_on_a_missing_list() {
+    let list = temp_path("inputs.txt");
+    let output = run(&["--input-list", list.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap()]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,17 @@
+mod common;
+use common::*;
+
+#[test]
+fn keeps_the_column_exactly_as_it_is() {
+    let input = write_temp("items.csv", "price,cost,count\n$5,$6,10\n$7,$8,20\n");
+    let conn = import(&input, &["--infer-types", "--strip-currency", "--keep-text", "price", "--keep-text", "count"]);
+    assert_eq!(column_types(&conn, "items"), columns(&[("price", "TEXT"), ("cost", "INTEGER"), ("count", "TEXT")]));
+    assert_eq!(query(&conn, "SELECT price, cost, typeof(count) FROM items ORDER BY rowid"), ["$5|6|text", "$7|8|text"]);
+}
+
+#[test]
+fn cant_be_transformed_as_well() {
+    let input = write_temp("items.csv", "name\nAnn\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--keep-text", "name", "--lowercase-column", "name"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,25 @@
+mod common;
+use common::*;
+
+use clap::Parser;
+use csv_to_sqlite::{import_csv, Arguments, ImportProgress, ImportStage};
+use rusqlite::Connection;
+
+#[test]
+fn imports_a_file_and_reports_progress() {
+    let path = write_temp("numbers.csv", "n\n1\n2\n3\n");
+    let args = Arguments::try_parse_from(["csv_to_sqlite".as_ref(), path.as_os_str()]).unwrap();
+    let conn = Connection::open_in_memory().unwrap();
+
+    let mut updates: Vec<ImportProgress> = vec![];
+    import_csv(&conn, &args, &path, "numbers", Some(&mut |progress| updates.push(progress))).unwrap();
+
+    assert_eq!(row_count(&conn, "numbers"), 3);
+
+    assert!(updates.iter().all(|progress| progress.table == "numbers"));
+    let last_read = updates.iter().rfind(|progress| progress.stage == ImportStage::Reading).unwrap();
+    assert_eq!(last_read.rows, 3);
+    assert!(last_read.bytes > 0);
+    let last = updates.last().unwrap();
+    assert_eq!((last.stage, last.rows, last.total_rows), (ImportStage::Inserting, 3, Some(3)));
+}
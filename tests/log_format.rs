@@ -0,0 +1,31 @@
+mod common;
+use common::*;
+
+#[test]
+fn json_lines_have_the_row_context_as_fields() {
+    let input = write_temp("people.csv", "name,zip\nann,1x\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--log-format", "json", "--validate", "zip:^[0-9]+$"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let logged = stderr(&output);
+    let line = logged.lines().find(|line| line.contains("doesn't match")).unwrap();
+    assert!(line.starts_with('{') && line.ends_with('}'), "{}", line);
+    assert!(line.contains(r#""level":"WARN""#), "{}", line);
+    assert!(line.contains(&format!(r#""file":"{}""#, input.display())), "{}", line);
+    assert!(line.contains(r#""table":"people""#) && line.contains(r#""column":"zip""#), "{}", line);
+    assert!(line.contains(r#""row":1"#), "{}", line);
+}
+
+#[test]
+fn text_is_the_default() {
+    let input = write_temp("people.csv", "name,zip\nann,1x\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--validate", "zip:^[0-9]+$"]);
+    assert!(stderr(&output).lines().all(|line| line.starts_with('[')), "{}", stderr(&output));
+}
+
+#[test]
+fn json_strings_are_escaped() {
+    let input = write_temp("people.csv", "name,zip\nann,\"1\"\"x\\\"\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--log-format", "json", "--validate", "zip:^[0-9]+$"]);
+    let logged = stderr(&output);
+    assert!(logged.contains(r#"'1\"x\\'"#), "{}", logged);
+}
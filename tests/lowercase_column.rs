@@ -0,0 +1,9 @@
+mod common;
+use common::*;
+
+#[test]
+fn lowercases_only_the_named_columns() {
+    let input = write_temp("places.csv", "name,country\nAnn,USA\nBob,usa\nCat,Usa\n");
+    let conn = import(&input, &["--lowercase-column", "country"]);
+    assert_eq!(query(&conn, "SELECT name, country FROM places ORDER BY rowid"), ["Ann|usa", "Bob|usa", "Cat|usa"]);
+}
@@ -0,0 +1,36 @@
+mod common;
+use common::*;
+
+/// Import a file with a --max-field-size, returning what happened.
+fn import_limited(contents: &str, limit: &str) -> std::process::Output {
+    let input = write_temp("fields.csv", contents);
+    run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--max-field-size", limit])
+}
+
+#[test]
+fn allows_records_bigger_than_the_limit() {
+    // Each field is 6 bytes at most, counting its quotes, but the records are much longer.
+    let output = import_limited("aaaa,bbbbbb,cccc\n\"dddd\",eeee,ffff\n", "6");
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn stops_at_a_field_bigger_than_the_limit() {
+    let output = import_limited("a,b\n1,22222\n", "4");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("--max-field-size"), "{}", stderr(&output));
+}
+
+#[test]
+fn stops_at_an_unclosed_quote() {
+    let rows = "1,2\n".repeat(100);
+    let output = import_limited(&format!("a,b\n\"never closed,{}", rows), "64");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("--max-field-size"), "{}", stderr(&output));
+}
+
+#[test]
+fn counts_delimiters_in_quotes_as_part_of_the_field() {
+    let output = import_limited("a,b\n\"1,2,3\",4\n", "4");
+    assert!(!output.status.success());
+}
@@ -0,0 +1,45 @@
+mod common;
+use common::*;
+
+/// A keyed table with two people in it, which has a column the CSV files don't.
+fn database() -> std::path::PathBuf {
+    let database = temp_path("out.db");
+    rusqlite::Connection::open(&database).unwrap().execute_batch(
+        "CREATE TABLE people (code TEXT PRIMARY KEY, name TEXT, age INTEGER, note TEXT);
+         INSERT INTO people VALUES ('a', 'ann', 30, 'kept'), ('b', 'bob', 40, 'kept');"
+    ).unwrap();
+    database
+}
+
+#[test]
+fn updates_existing_rows_and_inserts_new_ones() {
+    let database = database();
+    let input = write_temp("people.csv", "code,name,age\nb,bobby,41\nc,cat,50\nc,cath,51\n");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--infer-types", "--merge-into"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(query(&conn, "SELECT code, name, age, note FROM people ORDER BY code"), ["a|ann|30|kept", "b|bobby|41|kept", "c|cath|51|NULL"]);
+}
+
+#[test]
+fn needs_the_key_in_the_file() {
+    let database = database();
+    let input = write_temp("people.csv", "name,age\nbobby,41\n");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--merge-into"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("code"), "{}", stderr(&output));
+}
+
+#[test]
+fn needs_the_files_columns_in_the_table() {
+    let database = database();
+    let input = write_temp("people.csv", "code,city\nb,Leeds\n");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--merge-into"]);
+    assert!(!output.status.success());
+
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--merge-into", "--add-missing-columns"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(query(&conn, "SELECT code, name, city FROM people ORDER BY code"), ["a|ann|NULL", "b|bob|Leeds"]);
+}
@@ -0,0 +1,19 @@
+mod common;
+use common::*;
+
+#[test]
+fn imports_each_section_as_a_table() {
+    let input = write_temp("report.csv", "Sales\nregion,total\nnorth,10\nsouth,20\n\nregion,count\r\neast,3\r\n");
+    let conn = import(&input, &["--multi-section"]);
+    assert_eq!(query(&conn, "SELECT region, total FROM Sales ORDER BY rowid"), ["north|10", "south|20"]);
+    assert_eq!(query(&conn, "SELECT region, count FROM report_2"), ["east|3"]);
+}
+
+#[test]
+fn copes_with_bad_utf8_in_a_section() {
+    let input = temp_path("report.csv");
+    std::fs::write(&input, b"name,n\nann,1\nb\xffb,2\ncat,3\n\nname,n\ndan,4\n").unwrap();
+    let conn = import(&input, &["--multi-section"]);
+    assert_eq!(query(&conn, "SELECT name FROM report_1 ORDER BY rowid"), ["ann", "cat"]);
+    assert_eq!(query(&conn, "SELECT name FROM report_2"), ["dan"]);
+}
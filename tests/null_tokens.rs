@@ -0,0 +1,24 @@
+mod common;
+use common::*;
+
+#[test]
+fn each_column_can_have_its_own_null_token() {
+    let input = write_temp("counts.csv", "name,count,score\nann,-1,-1\nNA,3,NA\n");
+    let conn = import(&input, &["--infer-types", "--null-token", "NA", "--null-token-column", "count:-1"]);
+    assert_eq!(column_types(&conn, "counts"), columns(&[("name", "TEXT"), ("count", "INTEGER"), ("score", "INTEGER")]));
+    assert_eq!(query(&conn, "SELECT name, count, score FROM counts ORDER BY rowid"), ["ann|NULL|-1", "NULL|3|NULL"]);
+}
+
+#[test]
+fn a_column_can_have_several() {
+    let input = write_temp("counts.csv", "count\n-1\n?\n5\n");
+    let conn = import(&input, &["--infer-types", "--null-token-column", "count:-1", "--null-token-column", "count:?"]);
+    assert_eq!(column_values(&conn, "counts", "count"), values(&[None, None, Some("5")]));
+}
+
+#[test]
+fn fails_on_a_missing_column() {
+    let input = write_temp("counts.csv", "count\n1\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--null-token-column", "total:-1"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,43 @@
+mod common;
+use common::*;
+
+/// Import some shares of which one isn't a percentage, with an --on-type-error action.
+/// Returns what was logged, and the rows imported if the import worked.
+fn import_shares(action: &str) -> (String, Option<Vec<String>>) {
+    let input = write_temp("shares.csv", "name,share\nann,50%\nbob,lots\ncat,25%\n");
+    let database = temp_path("out.db");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--percent-column", "share", "--on-type-error", action, "--summary-only"]);
+    let rows = output.status.success().then(|| {
+        let conn = rusqlite::Connection::open(&database).unwrap();
+        query(&conn, "SELECT name, share FROM shares ORDER BY rowid")
+    });
+    (stderr(&output), rows)
+}
+
+#[test]
+fn stores_the_value_as_text() {
+    let (logged, rows) = import_shares("text");
+    assert_eq!(rows.unwrap(), ["ann|0.5", "bob|lots", "cat|0.25"]);
+    assert!(logged.contains("1 type mismatches (stored as text)"), "{}", logged);
+}
+
+#[test]
+fn stores_null() {
+    let (logged, rows) = import_shares("null");
+    assert_eq!(rows.unwrap(), ["ann|0.5", "bob|NULL", "cat|0.25"]);
+    assert!(logged.contains("1 type mismatches (stored as NULL)"), "{}", logged);
+}
+
+#[test]
+fn skips_the_row() {
+    let (logged, rows) = import_shares("skip");
+    assert_eq!(rows.unwrap(), ["ann|0.5", "cat|0.25"]);
+    assert!(logged.contains("1 type mismatches (their rows were skipped)"), "{}", logged);
+}
+
+#[test]
+fn aborts_the_import() {
+    let (logged, rows) = import_shares("abort");
+    assert!(rows.is_none());
+    assert!(logged.contains("can't store 'lots' in column 'share' as a percentage"), "{}", logged);
+}
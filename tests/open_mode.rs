@@ -0,0 +1,37 @@
+mod common;
+use common::*;
+
+/// Import a small file into `database` with an --open-mode.
+fn import_with_mode(database: &std::path::Path, mode: &str) -> std::process::Output {
+    let input = write_temp("numbers.csv", "n\n1\n2\n");
+    run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--open-mode", mode])
+}
+
+#[test]
+fn new_needs_a_new_file() {
+    let database = temp_path("out.db");
+    assert!(import_with_mode(&database, "new").status.success());
+    assert!(!import_with_mode(&database, "new").status.success());
+}
+
+#[test]
+fn existing_needs_an_existing_file() {
+    let database = temp_path("out.db");
+    assert!(!import_with_mode(&database, "existing").status.success());
+    assert!(!database.exists());
+
+    rusqlite::Connection::open(&database).unwrap();
+    assert!(import_with_mode(&database, "existing").status.success());
+    assert_eq!(row_count(&rusqlite::Connection::open(&database).unwrap(), "numbers"), 2);
+}
+
+#[test]
+fn read_only_changes_nothing() {
+    let database = temp_path("out.db");
+    rusqlite::Connection::open(&database).unwrap().execute_batch("CREATE TABLE kept (n);").unwrap();
+    let output = import_with_mode(&database, "read-only");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("read-only"), "{}", stderr(&output));
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(query(&conn, "SELECT name FROM sqlite_master"), ["kept"]);
+}
@@ -0,0 +1,18 @@
+mod common;
+use common::*;
+
+#[test]
+fn stores_percentages_as_fractions() {
+    let input = write_temp("shares.csv", "name,share\nann,12.5%\nbob,40%\n");
+    let conn = import(&input, &["--percent-column", "share"]);
+    assert_eq!(column_types(&conn, "shares"), columns(&[("name", "TEXT"), ("share", "REAL")]));
+    assert_eq!(query(&conn, "SELECT share FROM shares ORDER BY rowid"), ["0.125", "0.4"]);
+}
+
+#[test]
+fn warns_about_values_which_arent_percentages() {
+    let input = write_temp("shares.csv", "share\n12.5%\n40%\nlots\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--percent-column", "share"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("row #3") && stderr(&output).contains("'lots'"), "{}", stderr(&output));
+}
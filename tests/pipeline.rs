@@ -0,0 +1,32 @@
+mod common;
+use common::*;
+
+/// More than one batch of rows, with a few bad shares among them.
+fn numbers() -> std::path::PathBuf {
+    let mut contents = String::from("n,share\n");
+    for ii in 0..2500 {
+        if ii % 1000 == 999 {
+            contents.push_str(&format!("{},lots\n", ii));
+        }
+        else {
+            contents.push_str(&format!("{},{}%\n", ii, ii % 100));
+        }
+    }
+    write_temp("numbers.csv", &contents)
+}
+
+#[test]
+fn imports_the_same_rows_as_without_it() {
+    let input = numbers();
+    let expected = query(&import(&input, &["--infer-types", "--percent-column", "share"]), "SELECT * FROM numbers ORDER BY rowid");
+    let conn = import(&input, &["--infer-types", "--percent-column", "share", "--pipeline"]);
+    assert_eq!(query(&conn, "SELECT * FROM numbers ORDER BY rowid"), expected);
+    assert_eq!(expected.len(), 2500);
+}
+
+#[test]
+fn skips_rows_with_type_errors() {
+    let conn = import(&numbers(), &["--percent-column", "share", "--on-type-error", "skip", "--pipeline"]);
+    assert_eq!(row_count(&conn, "numbers"), 2498);
+    assert_eq!(query(&conn, "SELECT COUNT(*) FROM numbers WHERE n IN ('999', '1999')"), ["0"]);
+}
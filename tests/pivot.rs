@@ -0,0 +1,28 @@
+mod common;
+use common::*;
+
+/// A "long" file, where bob has no city and cat has no age.
+const LONG: &str = "entity,attribute,value\nann,age,30\nann,city,Leeds\nbob,age,40\ncat,city,York\n";
+
+#[test]
+fn makes_a_column_for_each_attribute() {
+    let input = write_temp("people.csv", LONG);
+    let conn = import(&input, &["--pivot", "entity,attribute,value", "--infer-types"]);
+    assert_eq!(column_types(&conn, "people"), columns(&[("entity", "TEXT"), ("age", "INTEGER"), ("city", "TEXT")]));
+    assert_eq!(query(&conn, "SELECT entity, age, city FROM people ORDER BY rowid"), ["ann|30|Leeds", "bob|40|NULL", "cat|NULL|York"]);
+}
+
+#[test]
+fn fails_on_a_missing_column() {
+    let input = write_temp("people.csv", LONG);
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--pivot", "entity,key,value"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("'key'"), "{}", stderr(&output));
+}
+
+#[test]
+fn needs_three_columns() {
+    let input = write_temp("people.csv", LONG);
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--pivot", "entity,attribute"]);
+    assert!(!output.status.success());
+}
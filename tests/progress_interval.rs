@@ -0,0 +1,20 @@
+mod common;
+use common::*;
+
+#[test]
+fn logs_progress_every_interval() {
+    let input = write_temp("numbers.csv", "n\n1\n2\n3\n4\n5\n");
+    let output = run_logging(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--progress-interval", "2"], "", "info");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let log = stderr(&output);
+    assert!(log.contains("2 rows") && log.contains("4 rows") && !log.contains(": 3 rows\n"), "{}", log);
+    assert!(log.contains("5 rows in total"), "{}", log);
+}
+
+#[test]
+fn can_be_turned_off() {
+    let input = write_temp("numbers.csv", "n\n1\n2\n3\n");
+    let output = run_logging(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--progress-interval", "0"], "", "info");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stderr(&output).contains("rows in total"), "{}", stderr(&output));
+}
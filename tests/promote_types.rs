@@ -0,0 +1,37 @@
+mod common;
+use common::*;
+use rusqlite::Connection;
+
+/// Import `first`, then append `second` to the same table with --promote-types, both with --infer-types.
+/// Both files are called `name`.
+fn append(name: &str, first: &str, second: &str, prepare: &str) -> Connection {
+    let database = temp_path("out.db");
+    Connection::open(&database).unwrap().execute_batch(prepare).unwrap();
+    for contents in [first, second] {
+        let input = write_temp(name, contents);
+        let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--infer-types", "--promote-types"]);
+        assert!(output.status.success(), "{}", stderr(&output));
+    }
+    Connection::open(&database).unwrap()
+}
+
+#[test]
+fn widens_columns_for_the_new_rows() {
+    let conn = append("numbers.csv", "n,word\n1,one\n", "n,word\n2.5,two\n", "");
+    assert_eq!(column_types(&conn, "numbers"), columns(&[("n", "REAL"), ("word", "TEXT")]));
+    assert_eq!(query(&conn, "SELECT n, typeof(n), word FROM numbers ORDER BY rowid"), ["1|real|one", "2.5|real|two"]);
+}
+
+#[test]
+fn copes_with_quotes_in_names() {
+    let conn = append(r#"o"neil.csv"#, "\"say \"\"n\"\"\"\n1\n", "\"say \"\"n\"\"\"\nx\n", "");
+    assert_eq!(column_types(&conn, r#"o"neil"#), columns(&[(r#"say "n""#, "TEXT")]));
+    assert_eq!(row_count(&conn, r#"o"neil"#), 2);
+}
+
+#[test]
+fn leaves_other_tables_alone() {
+    let conn = append("numbers.csv", "n\n1\n", "n\n2.5\n", "CREATE TABLE numbers_promoted (keep TEXT); INSERT INTO numbers_promoted VALUES ('me');");
+    assert_eq!(column_types(&conn, "numbers"), columns(&[("n", "REAL")]));
+    assert_eq!(query(&conn, "SELECT keep FROM numbers_promoted"), ["me"]);
+}
@@ -0,0 +1,36 @@
+mod common;
+use common::*;
+
+#[test]
+fn masks_values() {
+    let input = write_temp("people.csv", "email,n\nann@example.com,1\n,2\n");
+    let conn = import(&input, &["--redact", "email:mask"]);
+    assert_eq!(query(&conn, "SELECT email, n FROM people ORDER BY rowid"), ["***|1", "|2"]);
+}
+
+#[test]
+fn hashes_values_with_an_hmac_keyed_by_the_salt() {
+    let input = write_temp("people.csv", "email\nann@example.com\nann@example.com\n");
+    let conn = import(&input, &["--redact", "email:hash", "--redact-salt", "pepper"]);
+    let hash = "cea0615277db22b071c78ce70a4863989eee4f315e2224a79e29b3cf4055b7bf";
+    assert_eq!(query(&conn, "SELECT email FROM people ORDER BY rowid"), [hash, hash]);
+}
+
+#[test]
+fn keeps_values_out_of_the_dedup_report() {
+    let input = write_temp("people.csv", "email\nann@example.com\nann@example.com\n");
+    let report = temp_path("report.csv");
+    import(&input, &["--redact", "email:mask", "--dedup", "--dedup-report", report.to_str().unwrap()]);
+    assert!(std::fs::read_to_string(&report).unwrap().ends_with("3,***\n"));
+}
+
+#[test]
+fn only_gives_line_numbers_for_the_error_context() {
+    let input = temp_path("people.csv");
+    std::fs::write(&input, b"email,n\nann@example.com,1\nbob\xff@example.com,2\n").unwrap();
+    let database = temp_path("out.db");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--error-context", "1", "--redact", "email:mask"]);
+    let logged = stderr(&output);
+    assert!(logged.contains("at line 3 of"), "{}", logged);
+    assert!(!logged.contains("example.com"), "{}", logged);
+}
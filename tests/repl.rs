@@ -0,0 +1,68 @@
+mod common;
+use common::*;
+use rusqlite::Connection;
+
+/// Import a small table, then run the REPL over it with `input` on stdin.
+/// Returns what happened, and the database to look at afterwards.
+fn repl(input: &str, extra: &[&str]) -> (std::process::Output, Connection) {
+    let csv = write_temp("people.csv", "name,age\nann,30\nbob,40\n");
+    let database = temp_path("out.db");
+    let args = [&[csv.to_str().unwrap(), "--output", database.to_str().unwrap(), "--repl"], extra].concat();
+    let output = run_with_input(&args, input);
+    assert!(output.status.success(), "{}", stderr(&output));
+    (output, Connection::open(&database).unwrap())
+}
+
+#[test]
+fn prints_what_queries_return() {
+    let (output, _) = repl("SELECT name, age FROM people\n  ORDER BY name; SELECT COUNT(*) FROM people;\n.tables\n", &[]);
+    assert_eq!(stdout(&output), "ann\t30\nbob\t40\n2\npeople\n");
+}
+
+#[test]
+fn commits_with_dot_commit() {
+    let (_, conn) = repl("DELETE FROM people WHERE name = 'ann';\n.commit\nDELETE FROM people;\n", &["--repl-commit-on-exit", "never"]);
+    assert_eq!(query(&conn, "SELECT name FROM people"), ["bob"]);
+}
+
+#[test]
+fn follows_the_users_own_commit() {
+    let (_, conn) = repl("DELETE FROM people WHERE name = 'ann'; COMMIT; DELETE FROM people;\n", &["--repl-commit-on-exit", "never"]);
+    assert_eq!(query(&conn, "SELECT name FROM people"), ["bob"]);
+}
+
+#[test]
+fn keeps_changes_on_exit_when_asked_to() {
+    let (_, conn) = repl("UPDATE people SET age = 31 WHERE name = 'ann';\n", &["--repl-commit-on-exit", "always"]);
+    assert_eq!(query(&conn, "SELECT age FROM people WHERE name = 'ann'"), ["31"]);
+}
+
+#[test]
+fn asks_about_changes_on_exit() {
+    let (output, conn) = repl("DELETE FROM people;\n.quit\ny\n", &[]);
+    assert!(stderr(&output).contains("uncommitted changes"), "{}", stderr(&output));
+    assert_eq!(row_count(&conn, "people"), 0);
+
+    let (_, conn) = repl("DELETE FROM people;\n.quit\nn\n", &[]);
+    assert_eq!(row_count(&conn, "people"), 2);
+}
+
+#[test]
+fn doesnt_count_savepoints_or_reading_pragmas_as_changes() {
+    let (output, _) = repl("SAVEPOINT look; PRAGMA table_info(people); RELEASE look;\n", &[]);
+    assert!(!stderr(&output).contains("uncommitted changes"), "{}", stderr(&output));
+}
+
+#[test]
+fn counts_an_insert_which_returns_rows_as_a_change() {
+    let (output, conn) = repl("INSERT INTO people (name, age) VALUES ('cat', 50) RETURNING name;\n", &["--repl-commit-on-exit", "always"]);
+    assert_eq!(stdout(&output), "cat\n");
+    assert_eq!(row_count(&conn, "people"), 3);
+}
+
+#[test]
+fn carries_on_after_an_error() {
+    let (output, _) = repl("SELECT nothing FROM nowhere;\nSELECT name FROM people WHERE age = 40;\n", &[]);
+    assert!(stderr(&output).contains("Error:"), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "bob\n");
+}
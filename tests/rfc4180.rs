@@ -0,0 +1,41 @@
+mod common;
+use common::*;
+
+/// Import a file with --rfc4180, returning what happened.
+fn import_strict(contents: &str) -> std::process::Output {
+    let input = write_temp("strict.csv", contents);
+    run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--rfc4180"])
+}
+
+/// Check a file is turned down, with a reason.
+fn assert_rejected(contents: &str, reason: &str) {
+    let output = import_strict(contents);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains(reason), "{}", stderr(&output));
+}
+
+#[test]
+fn accepts_a_valid_file() {
+    let output = import_strict("name,quote\r\nann,\"said \"\"hi\"\", then\r\nleft\"\r\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn rejects_text_after_a_closing_quote() {
+    assert_rejected("name,n\r\n\"a\"b,c\r\n", "line 2: unexpected character after a closing quote");
+}
+
+#[test]
+fn rejects_a_quote_inside_an_unquoted_field() {
+    assert_rejected("name,n\r\na\"b,c\r\n", "line 2: quote in the middle of an unquoted field");
+}
+
+#[test]
+fn rejects_bare_line_feeds() {
+    assert_rejected("name,n\na,1\n", "line 1: line ends with LF rather than CRLF");
+}
+
+#[test]
+fn rejects_an_unclosed_quote() {
+    assert_rejected("name,n\r\n\"a,1\r\n", "quoted field is never closed");
+}
@@ -0,0 +1,30 @@
+mod common;
+use common::*;
+
+#[test]
+fn keeps_only_the_matching_rows() {
+    let input = write_temp("sales.csv", "region,price\nNA,150\nEU,50\nAS,200\nEU,120\n");
+    let conn = import(&input, &["--infer-types", "--row-filter-sql", "price > 100 AND region IN ('NA', 'EU')"]);
+    assert_eq!(query(&conn, "SELECT region, price FROM sales ORDER BY rowid"), ["NA|150", "EU|120"]);
+}
+
+#[test]
+fn leaves_existing_rows_alone_when_appending() {
+    let database = temp_path("out.db");
+    let first = write_temp("sales.csv", "price\n10\n");
+    assert!(run(&[first.to_str().unwrap(), "--output", database.to_str().unwrap(), "--infer-types"]).status.success());
+
+    let second = write_temp("sales.csv", "price\n20\n200\n");
+    let output = run(&[second.to_str().unwrap(), "--output", database.to_str().unwrap(), "--infer-types", "--append", "--row-filter-sql", "price > 100"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(query(&conn, "SELECT price FROM sales ORDER BY rowid"), ["10", "200"]);
+}
+
+#[test]
+fn fails_on_a_bad_expression() {
+    let input = write_temp("sales.csv", "price\n10\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--row-filter-sql", "cost > 1"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("cost"), "{}", stderr(&output));
+}
@@ -0,0 +1,30 @@
+mod common;
+use common::*;
+
+/// A header and rows 1 to 5, with a comment line after row 2.
+const NUMBERS: &str = "n\n1\n2\n# not a row\n3\n4\n5\n";
+
+#[test]
+fn imports_only_the_rows_in_range() {
+    let input = write_temp("numbers.csv", NUMBERS);
+    let conn = import(&input, &["--rows", "2:4"]);
+    assert_eq!(query(&conn, "SELECT n FROM numbers ORDER BY rowid"), ["2", "3", "4"]);
+}
+
+#[test]
+fn either_end_can_be_left_off() {
+    let input = write_temp("numbers.csv", NUMBERS);
+    let conn = import(&input, &["--rows", "4:"]);
+    assert_eq!(query(&conn, "SELECT n FROM numbers ORDER BY rowid"), ["4", "5"]);
+    let conn = import(&input, &["--rows", ":1"]);
+    assert_eq!(query(&conn, "SELECT n FROM numbers ORDER BY rowid"), ["1"]);
+}
+
+#[test]
+fn rejects_bad_ranges() {
+    let input = write_temp("numbers.csv", NUMBERS);
+    for range in ["3:2", "0:2", "two", "1-2"] {
+        let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--rows", range]);
+        assert!(!output.status.success(), "{} was accepted", range);
+    }
+}
@@ -0,0 +1,33 @@
+mod common;
+use common::*;
+
+/// A file with a header and the numbers 1 to `count`.
+fn numbers(count: usize) -> std::path::PathBuf {
+    let rows = (1..=count).map(|n| format!("{}\n", n)).collect::<String>();
+    write_temp("numbers.csv", &format!("n\n{}", rows))
+}
+
+#[test]
+fn picks_the_same_rows_for_a_seed() {
+    let input = numbers(100);
+    let first = query(&import(&input, &["--sample-rows", "5", "--seed", "7"]), "SELECT n FROM numbers ORDER BY rowid");
+    let second = query(&import(&input, &["--sample-rows", "5", "--seed", "7"]), "SELECT n FROM numbers ORDER BY rowid");
+    assert_eq!(first.len(), 5);
+    assert_eq!(first, second);
+
+    // The rows stay in the order they were in the file.
+    let numbers = first.iter().map(|n| n.parse::<u32>().unwrap()).collect::<Vec<u32>>();
+    assert!(numbers.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", numbers);
+}
+
+#[test]
+fn keeps_everything_from_a_short_file() {
+    let conn = import(&numbers(3), &["--sample-rows", "10"]);
+    assert_eq!(query(&conn, "SELECT n FROM numbers ORDER BY rowid"), ["1", "2", "3"]);
+}
+
+#[test]
+fn takes_a_huge_sample_size() {
+    let conn = import(&numbers(3), &["--sample-rows", &usize::MAX.to_string()]);
+    assert_eq!(row_count(&conn, "numbers"), 3);
+}
@@ -0,0 +1,31 @@
+mod common;
+use common::*;
+
+/// A schema file giving the table's columns, in order.
+fn schema() -> std::path::PathBuf {
+    write_temp("schema.txt", "# The contract\nid_code:INTEGER\nname\n\nscore:REAL\nnotes:TEXT\n")
+}
+
+#[test]
+fn conforms_the_columns_to_the_schema() {
+    let input = write_temp("people.csv", "name,extra,score,id_code\nann,x,1.5,7\nbob,y,oops,8\n");
+    let conn = import(&input, &["--column-order-from-header-file", schema().to_str().unwrap(), "--on-type-error", "null"]);
+    assert_eq!(column_types(&conn, "people"), columns(&[("id_code", "INTEGER"), ("name", "TEXT"), ("score", "REAL"), ("notes", "TEXT")]));
+    assert_eq!(query(&conn, "SELECT id_code, name, score, notes FROM people ORDER BY rowid"), ["7|ann|1.5|NULL", "8|bob|NULL|NULL"]);
+}
+
+#[test]
+fn can_reject_extra_columns() {
+    let input = write_temp("people.csv", "name,extra\nann,x\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--column-order-from-header-file", schema().to_str().unwrap(), "--reject-extra-columns"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("extra"), "{}", stderr(&output));
+}
+
+#[test]
+fn rejects_unknown_types() {
+    let input = write_temp("people.csv", "name\nann\n");
+    let bad = write_temp("schema.txt", "name:VARCHAR\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--column-order-from-header-file", bad.to_str().unwrap()]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,14 @@
+mod common;
+use common::*;
+
+#[test]
+fn scientific_integers_are_coerced() {
+    let conn = import_fixture("scientific.csv", &["--infer-types", "--coerce-scientific"]);
+    assert_eq!(row_count(&conn, "scientific"), 4);
+    assert_eq!(column_types(&conn, "scientific"), columns(&[("name", "TEXT"), ("population", "INTEGER"), ("ratio", "REAL")]));
+    assert_eq!(column_values(&conn, "scientific", "population"), values(&[Some("1000000"), Some("1230000000000000"), Some("-2500"), None]));
+
+    // Without --coerce-scientific, they're just reals.
+    let conn = import_fixture("scientific.csv", &["--infer-types"]);
+    assert_eq!(column_types(&conn, "scientific"), columns(&[("name", "TEXT"), ("population", "REAL"), ("ratio", "REAL")]));
+}
@@ -0,0 +1,33 @@
+mod common;
+use common::*;
+
+#[test]
+fn writes_a_database_per_file_and_a_master_script() {
+    let first = write_temp("first.csv", "n\n1\n");
+    let second = first.parent().unwrap().join("second.csv");
+    std::fs::write(&second, "n\n2\n3\n").unwrap();
+    let dir = temp_path("tables");
+    let output = run(&[first.to_str().unwrap(), second.to_str().unwrap(), "--separate-databases", dir.to_str().unwrap(), "--create-view", "everything AS SELECT n FROM first UNION ALL SELECT n FROM second"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    assert_eq!(row_count(&rusqlite::Connection::open(dir.join("first.db")).unwrap(), "first"), 1);
+    assert_eq!(row_count(&rusqlite::Connection::open(dir.join("second.db")).unwrap(), "second"), 2);
+
+    // The script attaches the databases by relative paths, so point them at the directory.
+    let script = std::fs::read_to_string(dir.join("master.sql")).unwrap()
+        .replace("ATTACH DATABASE '", &format!("ATTACH DATABASE '{}/", dir.display()));
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn.execute_batch(&script).unwrap();
+    assert_eq!(query(&conn, "SELECT n FROM everything ORDER BY n"), ["1", "2", "3"]);
+}
+
+#[test]
+fn writes_the_master_script_when_a_file_fails() {
+    let good = write_temp("good.csv", "n\n1\n");
+    let missing = good.parent().unwrap().join("missing.csv");
+    let dir = temp_path("tables");
+    let output = run(&[good.to_str().unwrap(), missing.to_str().unwrap(), "--separate-databases", dir.to_str().unwrap()]);
+    assert!(!output.status.success());
+    let script = std::fs::read_to_string(dir.join("master.sql")).unwrap();
+    assert!(script.contains("'good.db'") && !script.contains("missing"), "{}", script);
+}
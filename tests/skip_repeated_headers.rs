@@ -0,0 +1,26 @@
+mod common;
+use common::*;
+
+/// Two files' worth of rows, concatenated with both headers.
+const CONCATENATED: &str = "name,age\nann,30\nname,age\nbob,40\nname,age\n";
+
+#[test]
+fn drops_repeats_of_the_header() {
+    let input = write_temp("people.csv", CONCATENATED);
+    let database = temp_path("out.db");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--skip-repeated-headers"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let logged = stderr(&output);
+    assert!(logged.contains("header on line 3") && logged.contains("header on line 5"), "{}", logged);
+    assert!(logged.contains("dropped 2 repeats of the header"), "{}", logged);
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(query(&conn, "SELECT name, age FROM people ORDER BY rowid"), ["ann|30", "bob|40"]);
+}
+
+#[test]
+fn keeps_them_by_default() {
+    let input = write_temp("people.csv", CONCATENATED);
+    let conn = import(&input, &[]);
+    assert_eq!(row_count(&conn, "people"), 4);
+}
@@ -0,0 +1,28 @@
+mod common;
+use common::*;
+use rusqlite::Connection;
+
+#[test]
+fn writes_a_script_which_loads_into_another_database() {
+    let input = write_temp("people.csv", "name,age\nann,30\no'brien,40\n");
+    let script = temp_path("people.sql");
+    let output = run(&[input.to_str().unwrap(), "--output", script.to_str().unwrap(), "--output-format", "sql", "--infer-types"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // It can be run twice, since the table is dropped first.
+    let conn = Connection::open_in_memory().unwrap();
+    let script = std::fs::read_to_string(&script).unwrap();
+    conn.execute_batch(&script).unwrap();
+    conn.execute_batch(&script).unwrap();
+    assert_eq!(query(&conn, "SELECT name, age, typeof(age) FROM people ORDER BY rowid"), ["ann|30|integer", "o'brien|40|integer"]);
+}
+
+#[test]
+fn writes_the_script_to_stdout() {
+    let input = write_temp("people.csv", "name\nann\n");
+    let output = run(&[input.to_str().unwrap(), "--output", "-", "--output-format", "sql"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&stdout(&output)).unwrap();
+    assert_eq!(query(&conn, "SELECT name FROM people"), ["ann"]);
+}
@@ -0,0 +1,16 @@
+mod common;
+use common::*;
+
+#[test]
+fn writes_only_the_database_to_stdout() {
+    let input = write_temp("numbers.csv", "n\n1\n2\n");
+    let output = run_logging(&[input.to_str().unwrap(), "--output", "-"], "", "info");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(output.stdout.starts_with(b"SQLite format 3\0"));
+    assert!(!stderr(&output).is_empty());
+
+    let database = temp_path("out.db");
+    std::fs::write(&database, &output.stdout).unwrap();
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(query(&conn, "SELECT n FROM numbers ORDER BY rowid"), ["1", "2"]);
+}
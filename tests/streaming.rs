@@ -0,0 +1,62 @@
+mod common;
+use common::*;
+
+#[test]
+fn trailing_newline_makes_no_difference() {
+    for fixture in ["trailing_newline.csv", "no_trailing_newline.csv", "trailing_blank_line.csv"] {
+        let table = fixture.trim_end_matches(".csv");
+        let conn = import_fixture(fixture, &["--streaming"]);
+        assert_eq!(row_count(&conn, table), 3, "{}", fixture);
+        assert_eq!(column_values(&conn, table, "name"), values(&[Some("alpha"), Some("beta"), Some("gamma")]), "{}", fixture);
+    }
+}
+
+#[test]
+fn blank_values_before_the_end_are_kept() {
+    let conn = import_fixture("one_column_blanks.csv", &["--streaming"]);
+    assert_eq!(column_values(&conn, "one_column_blanks", "value"), values(&[Some("A"), Some(""), Some("  "), Some("B")]));
+}
+
+#[test]
+fn transforms_and_redacts_like_buffering() {
+    let extra = ["--infer-types", "--transform", "city: upper", "--redact", "phone:mask"];
+    let buffered = import_fixture("zip_codes.csv", &extra);
+    let streamed = import_fixture("zip_codes.csv", &[&extra[..], &["--streaming"]].concat());
+    assert_eq!(column_types(&streamed, "zip_codes"), column_types(&buffered, "zip_codes"));
+    for column in ["city", "zip", "population", "phone"] {
+        assert_eq!(column_values(&streamed, "zip_codes", column), column_values(&buffered, "zip_codes", column), "{}", column);
+    }
+    assert_eq!(column_values(&streamed, "zip_codes", "city"), values(&[Some("BOSTON"), Some("CAMBRIDGE"), Some("NEW YORK")]));
+    assert_eq!(column_values(&streamed, "zip_codes", "phone"), values(&[Some("***"); 3]));
+}
+
+#[test]
+fn checks_the_column_count() {
+    let output = run(&[fixture("zip_codes.csv").to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--streaming", "--min-columns", "5"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn inserts_in_batches_across_commits() {
+    let rows = (1..=2500).map(|n| format!("{},{}\n", n, n * 2)).collect::<String>();
+    let input = write_temp("numbers.csv", &format!("n,double\n{}", rows));
+    let conn = import(&input, &["--streaming", "--infer-types", "--batch-size", "300", "--commit-every", "1000"]);
+    assert_eq!(row_count(&conn, "numbers"), 2500);
+    assert_eq!(query(&conn, "SELECT SUM(n), SUM(double), typeof(n) FROM numbers"), ["3126250|6252500|integer"]);
+}
+
+#[test]
+fn commits_often_with_a_connection_pool() {
+    let rows = (1..=2500).map(|n| format!("{}\n", n)).collect::<String>();
+    let inputs = ["first.csv", "second.csv", "third.csv"].map(|name| write_temp(name, &format!("n\n{}", rows)));
+    let database = temp_path("out.db");
+    let mut args = inputs.iter().map(|input| input.to_str().unwrap()).collect::<Vec<&str>>();
+    args.extend(["--output", database.to_str().unwrap(), "--streaming", "--connection-pool", "3"]);
+    let output = run(&args);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    for table in ["first", "second", "third"] {
+        assert_eq!(row_count(&conn, table), 2500);
+    }
+}
@@ -0,0 +1,21 @@
+mod common;
+use common::*;
+
+#[test]
+fn strips_currency_from_numeric_columns() {
+    let input = write_temp("prices.csv", "item,price,count,note\nfish,\"$1,234.56\",€100,$5 off\nchips,£2,\"1,000\",none\n");
+    let conn = import(&input, &["--infer-types", "--strip-currency"]);
+    assert_eq!(column_types(&conn, "prices"), columns(&[("item", "TEXT"), ("price", "REAL"), ("count", "INTEGER"), ("note", "TEXT")]));
+    assert_eq!(query(&conn, "SELECT price, count, note FROM prices ORDER BY rowid"), ["1234.56|100|$5 off", "2|1000|none"]);
+}
+
+#[test]
+fn uses_the_given_symbols() {
+    let input = write_temp("prices.csv", "price\nR10\n$20\n");
+    let conn = import(&input, &["--infer-types", "--strip-currency", "--currency-symbols", "R"]);
+    assert_eq!(column_types(&conn, "prices"), columns(&[("price", "TEXT")]));
+
+    let input = write_temp("prices.csv", "price\nR10\nR20\n");
+    let conn = import(&input, &["--infer-types", "--strip-currency", "--currency-symbols", "R"]);
+    assert_eq!(query(&conn, "SELECT price FROM prices ORDER BY rowid"), ["10", "20"]);
+}
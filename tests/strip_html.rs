@@ -0,0 +1,9 @@
+mod common;
+use common::*;
+
+#[test]
+fn strips_tags_and_decodes_entities() {
+    let input = write_temp("menu.csv", "dish,note\n<b>Fish &amp; chips</b>,<i>hot</i>\n<p>Pie&nbsp;&lt;3&gt;</p>,a <b> b\n");
+    let conn = import(&input, &["--strip-html", "dish"]);
+    assert_eq!(query(&conn, "SELECT dish, note FROM menu ORDER BY rowid"), ["Fish & chips|<i>hot</i>", "Pie <3>|a <b> b"]);
+}
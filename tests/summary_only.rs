@@ -0,0 +1,27 @@
+mod common;
+use common::*;
+
+/// Two rows which aren't UTF-8, and a share which isn't a percentage.
+fn input() -> std::path::PathBuf {
+    let input = temp_path("shares.csv");
+    std::fs::write(&input, b"n,share\n1,2%\n\xff,3%\n\xfe,4%\n5,lots\n").unwrap();
+    input
+}
+
+#[test]
+fn counts_row_errors_instead_of_logging_them() {
+    let output = run(&[input().to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--percent-column", "share", "--summary-only"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let logged = stderr(&output);
+    assert!(logged.contains("3 row errors in total"), "{}", logged);
+    assert!(logged.contains("2 parse errors") && logged.contains("1 type mismatches"), "{}", logged);
+    assert!(!logged.contains("Error reading CSV file") && !logged.contains("'lots'"), "{}", logged);
+}
+
+#[test]
+fn logs_each_row_error_by_default() {
+    let output = run(&[input().to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--percent-column", "share"]);
+    let logged = stderr(&output);
+    assert_eq!(logged.matches("Error reading CSV file").count(), 2, "{}", logged);
+    assert!(logged.contains("'lots'") && !logged.contains("in total"), "{}", logged);
+}
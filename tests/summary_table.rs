@@ -0,0 +1,27 @@
+mod common;
+use common::*;
+
+#[test]
+fn records_how_each_file_went() {
+    let good = write_temp("good.csv", "n\n1\n2\n");
+    let missing = good.parent().unwrap().join("missing.csv");
+    let database = temp_path("out.db");
+    let output = run(&[good.to_str().unwrap(), missing.to_str().unwrap(), "--output", database.to_str().unwrap(), "--summary-table", "--skip-if-unchanged"]);
+    assert!(!output.status.success());
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(query(&conn, "SELECT table_name, status, rows, errors, message IS NULL FROM _import_runs ORDER BY id"), ["good|imported|2|0|1", "missing|failed|0|0|0"]);
+
+    // Later runs add to the table.
+    let output = run(&[good.to_str().unwrap(), "--output", database.to_str().unwrap(), "--summary-table", "--skip-if-unchanged"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(query(&conn, "SELECT table_name, status FROM _import_runs ORDER BY id"), ["good|imported", "missing|failed", "good|skipped"]);
+}
+
+#[test]
+fn counts_row_errors() {
+    let input = temp_path("numbers.csv");
+    std::fs::write(&input, b"n\n1\n\xff\n").unwrap();
+    let conn = import(&input, &["--summary-table"]);
+    assert_eq!(query(&conn, "SELECT status, rows, errors FROM _import_runs"), ["imported|1|1"]);
+}
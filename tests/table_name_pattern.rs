@@ -0,0 +1,26 @@
+mod common;
+use common::*;
+
+#[test]
+fn names_tables_from_the_pattern() {
+    let dir = temp_path("q1");
+    std::fs::create_dir(&dir).unwrap();
+    let first = dir.join("sales-north.csv");
+    let second = dir.join("sales south.csv");
+    std::fs::write(&first, "amount\n10\n").unwrap();
+    std::fs::write(&second, "amount\n20\n30\n").unwrap();
+    let database = temp_path("out.db");
+    let output = run(&[first.to_str().unwrap(), second.to_str().unwrap(), "--output", database.to_str().unwrap(), "--table-name-pattern", "{parent}.{basename}-{index}"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let conn = rusqlite::Connection::open(&database).unwrap();
+    assert_eq!(row_count(&conn, "q1_sales_north_1"), 1);
+    assert_eq!(row_count(&conn, "q1_sales_south_2"), 2);
+}
+
+#[test]
+fn names_which_start_with_a_digit_get_an_underscore() {
+    let input = write_temp("2024.csv", "amount\n10\n");
+    let conn = import(&input, &["--table-name-pattern", "{basename}"]);
+    assert_eq!(row_count(&conn, "_2024"), 1);
+}
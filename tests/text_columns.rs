@@ -0,0 +1,13 @@
+mod common;
+use common::*;
+
+#[test]
+fn codes_with_leading_zeros_stay_text() {
+    let conn = import_fixture("zip_codes.csv", &["--infer-types"]);
+    assert_eq!(row_count(&conn, "zip_codes"), 3);
+    assert_eq!(column_types(&conn, "zip_codes"), columns(&[("city", "TEXT"), ("zip", "TEXT"), ("population", "INTEGER"), ("phone", "INTEGER")]));
+    assert_eq!(column_values(&conn, "zip_codes", "zip"), values(&[Some("02134"), Some("02139"), Some("10001")]));
+
+    let conn = import_fixture("zip_codes.csv", &["--infer-types", "--text-columns", "population"]);
+    assert_eq!(column_types(&conn, "zip_codes"), columns(&[("city", "TEXT"), ("zip", "TEXT"), ("population", "TEXT"), ("phone", "INTEGER")]));
+}
@@ -0,0 +1,18 @@
+mod common;
+use common::*;
+
+#[test]
+fn trailing_newline_makes_no_difference() {
+    for fixture in ["trailing_newline.csv", "no_trailing_newline.csv", "trailing_blank_line.csv"] {
+        let table = fixture.trim_end_matches(".csv");
+        let conn = import_fixture(fixture, &[]);
+        assert_eq!(row_count(&conn, table), 3, "{}", fixture);
+        assert_eq!(column_values(&conn, table, "name"), values(&[Some("alpha"), Some("beta"), Some("gamma")]), "{}", fixture);
+    }
+}
+
+#[test]
+fn blank_values_before_the_end_are_kept() {
+    let conn = import_fixture("one_column_blanks.csv", &[]);
+    assert_eq!(column_values(&conn, "one_column_blanks", "value"), values(&[Some("A"), Some(""), Some("  "), Some("B")]));
+}
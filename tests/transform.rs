@@ -0,0 +1,26 @@
+mod common;
+use common::*;
+
+#[test]
+fn runs_the_operations_in_order() {
+    let input = write_temp("people.csv", "name,share\n  Ann Lee ,12.5%\n Bob,40%\n");
+    let conn = import(&input, &["--transform", "name: trim | upper | replace(' ', _)", "--transform", "share: trim | percent"]);
+    assert_eq!(column_types(&conn, "people"), columns(&[("name", "TEXT"), ("share", "REAL")]));
+    assert_eq!(query(&conn, "SELECT name, share FROM people ORDER BY rowid"), ["ANN_LEE|0.125", "BOB|0.4"]);
+}
+
+#[test]
+fn pipelines_for_a_column_run_in_the_order_given() {
+    let input = write_temp("people.csv", "name\nAnn\n");
+    let conn = import(&input, &["--transform", "name: lower", "--transform", "name: replace(a, o)"]);
+    assert_eq!(query(&conn, "SELECT name FROM people"), ["onn"]);
+}
+
+#[test]
+fn rejects_bad_pipelines() {
+    let input = write_temp("people.csv", "name\nAnn\n");
+    for transform in ["name: reverse", "name: percent | trim", "name trim"] {
+        let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--transform", transform]);
+        assert!(!output.status.success(), "{} was accepted", transform);
+    }
+}
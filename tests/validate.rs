@@ -0,0 +1,30 @@
+mod common;
+use common::*;
+
+#[test]
+fn warns_about_values_which_dont_match() {
+    let input = write_temp("people.csv", "name,zip\nann,12345\nbob,1234x\ncat,\n");
+    let database = temp_path("out.db");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--validate", "zip:^[0-9]{5}$"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let logged = stderr(&output);
+    assert!(logged.contains("row #2") && logged.contains("'1234x'") && !logged.contains("row #3"), "{}", logged);
+    assert_eq!(row_count(&rusqlite::Connection::open(&database).unwrap(), "people"), 3);
+}
+
+#[test]
+fn strict_stops_at_the_first_bad_value() {
+    let input = write_temp("people.csv", "name,zip\nann,12345\nbob,1234x\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--validate", "zip:^[0-9]{5}$", "--strict"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("'1234x'"), "{}", stderr(&output));
+}
+
+#[test]
+fn rejects_bad_validations() {
+    let input = write_temp("people.csv", "name,zip\nann,12345\n");
+    for validation in ["zip:(", "zip", "city:^a"] {
+        let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--validate", validation]);
+        assert!(!output.status.success(), "{} was accepted", validation);
+    }
+}
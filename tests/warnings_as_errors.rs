@@ -0,0 +1,27 @@
+mod common;
+use common::*;
+
+#[test]
+fn fails_if_anything_was_warned_about() {
+    let input = write_temp("people.csv", "name,zip\nann,12345\nbob,1234x\n");
+    let database = temp_path("out.db");
+    let output = run(&[input.to_str().unwrap(), "--output", database.to_str().unwrap(), "--validate", "zip:^[0-9]{5}$", "--warnings-as-errors"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("--warnings-as-errors"), "{}", stderr(&output));
+    // The rows still went in.
+    assert_eq!(row_count(&rusqlite::Connection::open(&database).unwrap(), "people"), 2);
+}
+
+#[test]
+fn counts_warnings_even_when_they_arent_logged() {
+    let input = write_temp("people.csv", "name,zip\nbob,1234x\n");
+    let output = run_logging(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--validate", "zip:^[0-9]{5}$", "--warnings-as-errors"], "", "error");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn passes_a_clean_import() {
+    let input = write_temp("people.csv", "name,zip\nann,12345\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--validate", "zip:^[0-9]{5}$", "--warnings-as-errors"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+}
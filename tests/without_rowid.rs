@@ -0,0 +1,26 @@
+mod common;
+use common::*;
+
+#[test]
+fn stores_the_table_without_rowid() {
+    let input = write_temp("codes.csv", "country,code,name\nGB,1,one\nGB,2,two\nFR,1,un\n");
+    let conn = import(&input, &["--primary-key", "country", "--primary-key", "code", "--without-rowid"]);
+    let schema = query(&conn, "SELECT sql FROM sqlite_master WHERE name = 'codes'").join("");
+    assert!(schema.contains("WITHOUT ROWID") && schema.contains(r#"PRIMARY KEY ("country", "code")"#), "{}", schema);
+    assert_eq!(query(&conn, "SELECT country, code, name FROM codes ORDER BY country, code"), ["FR|1|un", "GB|1|one", "GB|2|two"]);
+}
+
+#[test]
+fn needs_a_primary_key() {
+    let input = write_temp("codes.csv", "code\n1\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--without-rowid"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("--primary-key"), "{}", stderr(&output));
+}
+
+#[test]
+fn cant_be_filtered() {
+    let input = write_temp("codes.csv", "code\n1\n");
+    let output = run(&[input.to_str().unwrap(), "--output", temp_path("out.db").to_str().unwrap(), "--primary-key", "code", "--without-rowid", "--row-filter-sql", "code = 1"]);
+    assert!(!output.status.success());
+}